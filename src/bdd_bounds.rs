@@ -0,0 +1,107 @@
+use crate::prelude::*;
+
+use std::collections::BTreeSet;
+
+// approximation of the probability with the exact value
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Approx {
+    pub value: f64,
+    pub exact: f64,
+    pub rel_error: f64,
+}
+
+impl Approx {
+    pub fn new(value: f64, exact: f64) -> Self {
+        let rel_error = if exact != 0.0 {
+            (value - exact) / exact
+        } else if value == 0.0 {
+            0.0
+        } else {
+            f64::INFINITY
+        };
+        Approx {
+            value,
+            exact,
+            rel_error,
+        }
+    }
+}
+
+// probability that all the events in the set occur
+fn set_prob(set: &[String], pv: &HashMap<String, f64>) -> f64 {
+    set.iter().map(|x| *pv.get(x).unwrap_or(&0.0)).product()
+}
+
+// sum of the probabilities of cut sets
+pub fn rare_event(cuts: &[Vec<String>], pv: &HashMap<String, f64>) -> f64 {
+    cuts.iter().map(|c| set_prob(c, pv)).sum()
+}
+
+// min-cut upper bound; it is also the upper bound of Esary-Proschan
+pub fn mcub(cuts: &[Vec<String>], pv: &HashMap<String, f64>) -> f64 {
+    1.0 - cuts.iter().map(|c| 1.0 - set_prob(c, pv)).product::<f64>()
+}
+
+// lower bound of Esary-Proschan from path sets, i.e., the sets of events whose non-occurrence
+// prevents the top event
+pub fn ep_lower(paths: &[Vec<String>], pv: &HashMap<String, f64>) -> f64 {
+    paths
+        .iter()
+        .map(|p| 1.0 - p.iter().map(|x| 1.0 - *pv.get(x).unwrap_or(&0.0)).product::<f64>())
+        .product()
+}
+
+// inclusion-exclusion truncated after the terms of k cut sets
+pub fn inclusion_exclusion(cuts: &[Vec<String>], pv: &HashMap<String, f64>, k: usize) -> f64 {
+    fn visit(
+        cuts: &[Vec<String>],
+        pv: &HashMap<String, f64>,
+        start: usize,
+        depth: usize,
+        k: usize,
+        union: &BTreeSet<String>,
+    ) -> f64 {
+        let mut result = 0.0;
+        for i in start..cuts.len() {
+            let mut u = union.clone();
+            u.extend(cuts[i].iter().cloned());
+            let p: f64 = u.iter().map(|x| *pv.get(x).unwrap_or(&0.0)).product();
+            let sign = if depth % 2 == 0 { 1.0 } else { -1.0 };
+            result += sign * p;
+            if depth + 1 < k {
+                result += visit(cuts, pv, i + 1, depth + 1, k, &u);
+            }
+        }
+        result
+    }
+    visit(cuts, pv, 0, 0, k, &BTreeSet::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_bounds() {
+        let mut bss = BddMgr::new();
+        let f = bss.rpn("a b & b c & | a c & |").unwrap();
+        let mut pv = HashMap::new();
+        pv.insert("a".to_string(), 0.1);
+        pv.insert("b".to_string(), 0.1);
+        pv.insert("c".to_string(), 0.1);
+        let cuts: Vec<Vec<String>> = f.minpath().unwrap().zdd_extract(&[true]).collect();
+        assert_eq!(cuts.len(), 3);
+        let exact = 0.028;
+        let r = f.rare_event(&cuts, &pv);
+        assert!((r.exact - exact).abs() < 1.0e-12);
+        assert!((r.value - 0.03).abs() < 1.0e-12);
+        assert!((r.rel_error - 0.002 / 0.028).abs() < 1.0e-10);
+        let m = f.mcub(&cuts, &pv);
+        assert!((m.value - (1.0 - 0.99f64.powi(3))).abs() < 1.0e-12);
+        let (lower, upper) = f.esary_proschan(&cuts, &cuts, &pv);
+        assert!((lower.value - 0.19f64.powi(3)).abs() < 1.0e-12);
+        assert!(lower.value <= exact && exact <= upper.value);
+        assert!((f.inclusion_exclusion(&cuts, &pv, 2).value - 0.027).abs() < 1.0e-12);
+        assert!((f.inclusion_exclusion(&cuts, &pv, 3).value - exact).abs() < 1.0e-12);
+    }
+}
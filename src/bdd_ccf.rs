@@ -0,0 +1,218 @@
+use crate::prelude::*;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CcfModel {
+    // beta
+    BetaFactor(f64),
+    // alpha_1, ..., alpha_m
+    AlphaFactor(Vec<f64>),
+    // beta, gamma, delta, ... (rho_2, ..., rho_m)
+    Mgl(Vec<f64>),
+}
+
+// common cause failure group; each member fails by its independent event or
+// by one of the CCF events of the subsets containing it
+#[derive(Debug, Clone)]
+pub struct CcfGroup {
+    name: String,
+    members: Vec<String>,
+    model: CcfModel,
+    qt: f64,
+}
+
+fn binomial(n: usize, k: usize) -> f64 {
+    let mut result = 1.0;
+    for i in 0..k {
+        result = result * (n - i) as f64 / (i + 1) as f64;
+    }
+    result
+}
+
+fn combinations(m: usize, k: usize) -> Vec<Vec<usize>> {
+    if k == 0 {
+        return vec![Vec::new()];
+    }
+    let mut result = Vec::new();
+    for last in (k - 1)..m {
+        for mut x in combinations(last, k - 1).into_iter() {
+            x.push(last);
+            result.push(x);
+        }
+    }
+    result
+}
+
+impl CcfGroup {
+    // qt is the total failure probability of each member
+    pub fn new(name: &str, members: &[&str], model: CcfModel, qt: f64) -> Result<Self, String> {
+        let m = members.len();
+        if m < 2 {
+            return Err(format!("CCF group {} needs two or more members", name));
+        }
+        for (i, x) in members.iter().enumerate() {
+            if members[..i].contains(x) {
+                return Err(format!("CCF group {} has the member {} twice", name, x));
+            }
+        }
+        let unit = |x: &f64| (0.0..=1.0).contains(x);
+        if !unit(&qt) {
+            return Err(format!("The probability {} of CCF group {} is not in [0,1]", qt, name));
+        }
+        match &model {
+            CcfModel::BetaFactor(beta) if !unit(beta) => {
+                return Err(format!("The beta factor {} of CCF group {} is not in [0,1]", beta, name));
+            }
+            CcfModel::AlphaFactor(alpha) if alpha.len() != m => {
+                return Err(format!("CCF group {} needs {} alpha factors", name, m));
+            }
+            CcfModel::AlphaFactor(alpha) => {
+                // alpha_t is the denominator of qk
+                if alpha.iter().any(|a| a.is_nan() || *a < 0.0) {
+                    return Err(format!("The alpha factors of CCF group {} should be non-negative", name));
+                }
+                let alpha_t: f64 = alpha.iter().enumerate().map(|(i, a)| (i + 1) as f64 * a).sum();
+                if alpha_t <= 0.0 {
+                    return Err(format!("The alpha factors of CCF group {} are all zero", name));
+                }
+            }
+            CcfModel::Mgl(rho) if rho.len() != m - 1 => {
+                return Err(format!("CCF group {} needs {} MGL parameters", name, m - 1));
+            }
+            CcfModel::Mgl(rho) if !rho.iter().all(unit) => {
+                return Err(format!("The MGL parameters of CCF group {} should be in [0,1]", name));
+            }
+            _ => (),
+        }
+        Ok(CcfGroup {
+            name: name.to_string(),
+            members: members.iter().map(|x| x.to_string()).collect(),
+            model,
+            qt,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn members(&self) -> &[String] {
+        &self.members
+    }
+
+    // probability of a basic event failing a specific set of k members
+    pub fn qk(&self, k: usize) -> f64 {
+        let m = self.members.len();
+        if k == 0 || k > m {
+            return 0.0;
+        }
+        match &self.model {
+            CcfModel::BetaFactor(beta) => {
+                if k == 1 {
+                    (1.0 - beta) * self.qt
+                } else if k == m {
+                    beta * self.qt
+                } else {
+                    0.0
+                }
+            }
+            CcfModel::AlphaFactor(alpha) => {
+                let alpha_t: f64 = alpha.iter().enumerate().map(|(i, a)| (i + 1) as f64 * a).sum();
+                k as f64 / binomial(m - 1, k - 1) * alpha[k - 1] / alpha_t * self.qt
+            }
+            CcfModel::Mgl(rho) => {
+                // rho_1 = 1 and rho_{m+1} = 0
+                let prod: f64 = rho[..k - 1].iter().product();
+                let next = if k < m { rho[k - 1] } else { 0.0 };
+                prod * (1.0 - next) / binomial(m - 1, k - 1) * self.qt
+            }
+        }
+    }
+
+    pub fn event_name(&self, members: &[&str]) -> String {
+        format!("{}[{}]", self.name, members.join(","))
+    }
+
+    // basic events (name, failed members); a single member event is the independent failure
+    pub fn events(&self) -> Vec<(String, Vec<String>)> {
+        let m = self.members.len();
+        let mut result = Vec::new();
+        for k in 1..=m {
+            if let CcfModel::BetaFactor(_) = self.model {
+                if k != 1 && k != m {
+                    continue;
+                }
+            }
+            for c in combinations(m, k).into_iter() {
+                let xs: Vec<&str> = c.iter().map(|&i| self.members[i].as_str()).collect();
+                result.push((self.event_name(&xs), xs.iter().map(|x| x.to_string()).collect()));
+            }
+        }
+        result
+    }
+
+    pub fn probabilities(&self) -> HashMap<String, f64> {
+        self.events()
+            .into_iter()
+            .map(|(name, xs)| (name, self.qk(xs.len())))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bss::BddMgr;
+
+    #[test]
+    fn test_ccf_beta() {
+        let mut bss = BddMgr::new();
+        let group = CcfGroup::new("pumps", &["a", "b", "c"], CcfModel::BetaFactor(0.1), 0.01).unwrap();
+        bss.defccf(&group).unwrap();
+        let f = bss.rpn("a b & b c & | a c & |").unwrap();
+        let pv = bss.ccf_pv(&HashMap::new()).unwrap();
+        assert_eq!(pv.len(), 4);
+        let p1 = 0.009;
+        let pc = 0.001;
+        let exact = pc + (1.0 - pc) * (3.0 * p1 * p1 * (1.0 - p1) + p1 * p1 * p1);
+        assert!((f.prob(&pv, &[true]) - exact).abs() < 1.0e-12);
+        // the other events are kept, but the CCF events cannot be given by the user
+        let mut user = HashMap::new();
+        user.insert("d".to_string(), 0.5);
+        assert_eq!(bss.ccf_pv(&user).unwrap()["d"], 0.5);
+        user.insert("pumps[a,b,c]".to_string(), 0.5);
+        assert!(bss.ccf_pv(&user).is_err());
+        // a member is no longer a variable
+        let mut user = HashMap::new();
+        user.insert("a".to_string(), 0.5);
+        assert!(bss.ccf_pv(&user).is_err());
+    }
+
+    #[test]
+    fn test_ccf_alpha_mgl() {
+        let alpha = CcfGroup::new("g", &["a", "b", "c", "d"], CcfModel::AlphaFactor(vec![0.95, 0.03, 0.015, 0.005]), 0.02).unwrap();
+        let mgl = CcfGroup::new("g", &["a", "b", "c", "d"], CcfModel::Mgl(vec![0.1, 0.3, 0.5]), 0.02).unwrap();
+        for group in [alpha, mgl] {
+            let pv = group.probabilities();
+            assert_eq!(pv.len(), 15);
+            let total: f64 = group
+                .events()
+                .iter()
+                .filter(|(_, xs)| xs.contains(&"a".to_string()))
+                .map(|(name, _)| pv[name])
+                .sum();
+            assert!((total - 0.02).abs() < 1.0e-12);
+        }
+    }
+
+    #[test]
+    fn test_ccf_invalid() {
+        let members = ["a", "b", "c"];
+        assert!(CcfGroup::new("g", &members, CcfModel::BetaFactor(1.5), 0.01).is_err());
+        assert!(CcfGroup::new("g", &members, CcfModel::BetaFactor(0.1), -0.01).is_err());
+        assert!(CcfGroup::new("g", &members, CcfModel::BetaFactor(0.1), f64::NAN).is_err());
+        assert!(CcfGroup::new("g", &members, CcfModel::AlphaFactor(vec![0.0, 0.0, 0.0]), 0.01).is_err());
+        assert!(CcfGroup::new("g", &members, CcfModel::AlphaFactor(vec![1.0, -0.1, 0.1]), 0.01).is_err());
+        assert!(CcfGroup::new("g", &members, CcfModel::Mgl(vec![0.1, 2.0]), 0.01).is_err());
+        assert!(CcfGroup::new("g", &["a", "b", "a"], CcfModel::BetaFactor(0.1), 0.01).is_err());
+    }
+}
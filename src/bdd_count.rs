@@ -19,22 +19,39 @@ pub fn node_count<T>(
 where
     T: Add<Output = T> + Clone + From<u32>,
 {
-    let key = node;
-    if cache.contains(&key) {
-        return (T::from(0), T::from(0), T::from(1));
-    }
-    let result = match dd.get_node(node).unwrap() {
-        bdd::Node::One | bdd::Node::Zero | bdd::Node::Undet => {
-            (T::from(0), T::from(1), T::from(1))
+    // every visit counts an edge; the first visit of a node counts the node itself
+    let mut nn = T::from(0);
+    let mut nv = T::from(0);
+    let mut ne = T::from(0);
+    let mut next_stack = vec![node];
+    while let Some(f) = next_stack.pop() {
+        ne = ne + T::from(1);
+        if !cache.insert(f) {
+            continue;
         }
-        bdd::Node::NonTerminal(fnode) => {
-            let (n0, v0, e0): (T, T, T) = node_count(dd, fnode[0], cache);
-            let (n1, v1, e1): (T, T, T) = node_count(dd, fnode[1], cache);
-            (n0 + n1 + T::from(1), v0 + v1, e0 + e1 + T::from(1))
+        match dd.get_node(f).unwrap() {
+            bdd::Node::One | bdd::Node::Zero | bdd::Node::Undet => {
+                nv = nv + T::from(1);
+            }
+            bdd::Node::NonTerminal(fnode) => {
+                nn = nn + T::from(1);
+                next_stack.push(fnode[1]);
+                next_stack.push(fnode[0]);
+            }
         }
-    };
-    cache.insert(key);
-    result
+    }
+    (nn, nv, ne)
+}
+
+fn terminal_count<T>(ss: &[bool], value: bool) -> T
+where
+    T: From<u32>,
+{
+    if ss.contains(&value) {
+        T::from(1)
+    } else {
+        T::from(0)
+    }
 }
 
 pub fn bdd_count<T>(
@@ -46,50 +63,42 @@ pub fn bdd_count<T>(
 where
     T: Add<Output = T> + Clone + From<u32> + Mul<Output = T>,
 {
-    let key = node;
-    if let Some(x) = cache.get(&key) {
-        return x.clone();
-    }
-    let result = match dd.get_node(node).unwrap() {
-        bdd::Node::One => {
-            if ss.contains(&true) {
-                T::from(1)
-            } else {
-                T::from(0)
-            }
+    let mut next_stack = vec![(node, false)];
+    while let Some((f, expanded)) = next_stack.pop() {
+        if cache.contains_key(&f) {
+            continue;
         }
-        bdd::Node::Zero => {
-            if ss.contains(&false) {
-                T::from(1)
-            } else {
-                T::from(0)
+        match dd.get_node(f).unwrap() {
+            bdd::Node::One => {
+                cache.insert(f, terminal_count(ss, true));
             }
-        }
-        bdd::Node::NonTerminal(fnode) => {
-            let mut result = T::from(0);
-            let current_level = dd.level(node).unwrap();
-            if let Some(next_level) = dd.level(fnode[0]) {
-                result = result
-                    + power(T::from(2), current_level - next_level - 1)
-                        * bdd_count(dd, ss, fnode[0], cache);
-            } else {
-                result =
-                    result + power(T::from(2), current_level) * bdd_count(dd, ss, fnode[0], cache);
+            bdd::Node::Zero => {
+                cache.insert(f, terminal_count(ss, false));
             }
-            if let Some(next_level) = dd.level(fnode[1]) {
-                result = result
-                    + power(T::from(2), current_level - next_level - 1)
-                        * bdd_count(dd, ss, fnode[1], cache);
-            } else {
-                result =
-                    result + power(T::from(2), current_level) * bdd_count(dd, ss, fnode[1], cache);
+            bdd::Node::NonTerminal(fnode) if expanded => {
+                let mut result = T::from(0);
+                let current_level = dd.level(f).unwrap();
+                for &x in fnode.iter() {
+                    // the variables skipped by the edge take both values
+                    let skipped = match dd.level(x) {
+                        Some(next_level) => current_level - next_level - 1,
+                        None => current_level,
+                    };
+                    result = result + power(T::from(2), skipped) * cache.get(&x).unwrap().clone();
+                }
+                cache.insert(f, result);
+            }
+            bdd::Node::NonTerminal(fnode) => {
+                next_stack.push((f, true));
+                next_stack.push((fnode[1], false));
+                next_stack.push((fnode[0], false));
+            }
+            bdd::Node::Undet => {
+                cache.insert(f, T::from(0));
             }
-            result
         }
-        bdd::Node::Undet => T::from(0),
-    };
-    cache.insert(key, result.clone());
-    result
+    }
+    cache.get(&node).unwrap().clone()
 }
 
 pub fn zdd_count<T>(
@@ -101,30 +110,32 @@ pub fn zdd_count<T>(
 where
     T: Add<Output = T> + Clone + From<u32>,
 {
-    let key = node;
-    if let Some(x) = cache.get(&key) {
-        return x.clone();
-    }
-    let result = match dd.get_node(node).unwrap() {
-        bdd::Node::One => {
-            if ss.contains(&true) {
-                T::from(1)
-            } else {
-                T::from(0)
-            }
+    let mut next_stack = vec![(node, false)];
+    while let Some((f, expanded)) = next_stack.pop() {
+        if cache.contains_key(&f) {
+            continue;
         }
-        bdd::Node::Zero => {
-            if ss.contains(&false) {
-                T::from(1)
-            } else {
-                T::from(0)
+        match dd.get_node(f).unwrap() {
+            bdd::Node::One => {
+                cache.insert(f, terminal_count(ss, true));
+            }
+            bdd::Node::Zero => {
+                cache.insert(f, terminal_count(ss, false));
+            }
+            bdd::Node::NonTerminal(fnode) if expanded => {
+                let low = cache.get(&fnode[0]).unwrap().clone();
+                let high = cache.get(&fnode[1]).unwrap().clone();
+                cache.insert(f, low + high);
+            }
+            bdd::Node::NonTerminal(fnode) => {
+                next_stack.push((f, true));
+                next_stack.push((fnode[1], false));
+                next_stack.push((fnode[0], false));
+            }
+            bdd::Node::Undet => {
+                cache.insert(f, T::from(0));
             }
         }
-        bdd::Node::NonTerminal(fnode) => {
-            zdd_count(dd, ss, fnode[0], cache) + zdd_count(dd, ss, fnode[1], cache)
-        }
-        bdd::Node::Undet => T::from(0),
-    };
-    cache.insert(key, result.clone());
-    result
+    }
+    cache.get(&node).unwrap().clone()
 }
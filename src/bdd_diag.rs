@@ -0,0 +1,183 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::prelude::*;
+
+// one step of the traversal over a minimal solution diagram
+pub enum Step {
+    Leaf(bool),
+    Inner(String, Vec<NodeId>),
+}
+
+// a partial explanation ordered by prob * (the best completion below node)
+struct Candidate {
+    priority: f64,
+    prob: f64,
+    node: NodeId,
+    path: Vec<(String, usize)>,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority
+            .partial_cmp(&other.priority)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+// best-first search of the n most probable paths to a solution terminal;
+// weights[label][i] is the probability of the event attached to the i-th edge.
+// A path records only the edges other than 0, which stand for "nothing is required"
+pub fn best_first<F>(
+    step: &F,
+    root: NodeId,
+    weights: &HashMap<String, Vec<f64>>,
+    n: usize,
+) -> Vec<(Vec<(String, usize)>, f64)>
+where
+    F: Fn(NodeId) -> Step,
+{
+    let mut cache = BddHashMap::default();
+    let mut result = Vec::new();
+    let mut heap = BinaryHeap::new();
+    let b = bound(step, root, weights, &mut cache);
+    if b > 0.0 {
+        heap.push(Candidate {
+            priority: b,
+            prob: 1.0,
+            node: root,
+            path: Vec::new(),
+        });
+    }
+    while let Some(c) = heap.pop() {
+        if result.len() >= n {
+            break;
+        }
+        match step(c.node) {
+            Step::Leaf(true) => result.push((c.path, c.prob)),
+            Step::Leaf(false) => (),
+            Step::Inner(label, children) => {
+                for (i, x) in children.into_iter().enumerate() {
+                    let p = c.prob * weight(weights, &label, i);
+                    let priority = p * bound(step, x, weights, &mut cache);
+                    if priority > 0.0 {
+                        let mut path = c.path.clone();
+                        if i != 0 {
+                            path.push((label.clone(), i));
+                        }
+                        heap.push(Candidate {
+                            priority,
+                            prob: p,
+                            node: x,
+                            path,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    result
+}
+
+fn weight(weights: &HashMap<String, Vec<f64>>, label: &str, i: usize) -> f64 {
+    match weights.get(label) {
+        Some(w) => w[i],
+        None if i == 0 => 1.0,
+        None => 0.0,
+    }
+}
+
+// the largest probability of a path from node to a solution terminal
+fn bound<F>(
+    step: &F,
+    node: NodeId,
+    weights: &HashMap<String, Vec<f64>>,
+    cache: &mut BddHashMap<NodeId, f64>,
+) -> f64
+where
+    F: Fn(NodeId) -> Step,
+{
+    if let Some(&x) = cache.get(&node) {
+        return x;
+    }
+    let result = match step(node) {
+        Step::Leaf(true) => 1.0,
+        Step::Leaf(false) => 0.0,
+        Step::Inner(label, children) => {
+            let mut result: f64 = 0.0;
+            for (i, x) in children.into_iter().enumerate() {
+                let tmp = weight(weights, &label, i) * bound(step, x, weights, cache);
+                result = result.max(tmp);
+            }
+            result
+        }
+    };
+    cache.insert(node, result);
+    result
+}
+
+// the n most probable minimal cut sets in the minimal solution diagram `node`
+// together with the probabilities that all the members are failed
+pub fn top_n(dd: &BddManager, node: NodeId, pv: &HashMap<String, f64>, n: usize) -> Vec<(Vec<String>, f64)> {
+    let weights: HashMap<String, Vec<f64>> = pv
+        .iter()
+        .map(|(k, &p)| (k.clone(), vec![1.0, p]))
+        .collect();
+    let step = |x: NodeId| match dd.get_node(x).unwrap() {
+        bdd::Node::NonTerminal(fnode) => {
+            Step::Inner(dd.label(x).unwrap().to_string(), vec![fnode[0], fnode[1]])
+        }
+        bdd::Node::One => Step::Leaf(true),
+        _ => Step::Leaf(false),
+    };
+    best_first(&step, node, &weights, n)
+        .into_iter()
+        .map(|(path, p)| (path.into_iter().map(|(x, _)| x).collect(), p))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bss::BddMgr;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_top_explanations() {
+        let mut bss = BddMgr::new();
+        let f = bss.rpn("a b & b c & | a c & | d |").unwrap();
+        let mut pv = HashMap::new();
+        pv.insert("a".to_string(), 0.1);
+        pv.insert("b".to_string(), 0.2);
+        pv.insert("c".to_string(), 0.3);
+        pv.insert("d".to_string(), 0.01);
+        let total = f.prob(&pv, &[true]);
+        let result = f.top_explanations(&pv, 3).unwrap();
+        for (s, p) in result.iter() {
+            println!("{:?} {}", s, p);
+        }
+        assert_eq!(result.len(), 3);
+        let mut first = result[0].0.clone();
+        first.sort();
+        assert_eq!(first, vec!["b".to_string(), "c".to_string()]);
+        assert!((result[0].1 - 0.06 / total).abs() < 1.0e-12);
+        assert!((result[1].1 - 0.03 / total).abs() < 1.0e-12);
+        assert!((result[2].1 - 0.02 / total).abs() < 1.0e-12);
+        let all = f.top_explanations(&pv, 10).unwrap();
+        assert_eq!(all.len(), 4);
+        assert!((all[3].1 - 0.01 / total).abs() < 1.0e-12);
+    }
+}
@@ -0,0 +1,134 @@
+use crate::bdd_monotone::{find_pair, variables, Step};
+use crate::prelude::*;
+
+// an assignment on which a relation between two functions fails, with their values.
+// A variable missing in the assignment may take any value
+#[derive(Debug, Clone)]
+pub struct Witness<T> {
+    pub assignment: HashMap<String, usize>,
+    pub values: (T, T),
+}
+
+// one step over the BDD for find_pair and variables
+pub(crate) fn step(dd: &BddManager, x: NodeId) -> Step<bool> {
+    match dd.get_node(x).unwrap() {
+        bdd::Node::NonTerminal(fnode) => Step::Inner(
+            dd.label(x).unwrap().to_string(),
+            dd.level(x).unwrap(),
+            vec![fnode[0], fnode[1]],
+        ),
+        bdd::Node::Zero => Step::Leaf(Some(false)),
+        bdd::Node::One => Step::Leaf(Some(true)),
+        bdd::Node::Undet => Step::Leaf(None),
+    }
+}
+
+// the labels of the variables in the BDD in ascending order of levels
+fn labels(dd: &BddManager, node: NodeId) -> Vec<String> {
+    variables(&|x| step(dd, x), node).into_iter().map(|(_, x, _)| x).collect()
+}
+
+// copy the BDD into another manager; vars gives the variable of each label in dd2
+fn copy(
+    dd: &BddManager,
+    node: NodeId,
+    dd2: &mut BddManager,
+    vars: &HashMap<String, NodeId>,
+    cache: &mut BddHashMap<NodeId, NodeId>,
+) -> NodeId {
+    let mut next_stack = vec![(node, false)];
+    while let Some((f, expanded)) = next_stack.pop() {
+        if cache.contains_key(&f) {
+            continue;
+        }
+        match dd.get_node(f).unwrap() {
+            bdd::Node::Zero => {
+                cache.insert(f, dd2.zero());
+            }
+            bdd::Node::One => {
+                cache.insert(f, dd2.one());
+            }
+            bdd::Node::NonTerminal(fnode) if expanded => {
+                let x = vars[dd.label(f).unwrap()];
+                let result = dd2.ite(x, cache[&fnode[1]], cache[&fnode[0]]);
+                cache.insert(f, result);
+            }
+            bdd::Node::NonTerminal(fnode) => {
+                next_stack.push((f, true));
+                next_stack.push((fnode[1], false));
+                next_stack.push((fnode[0], false));
+            }
+            bdd::Node::Undet => panic!("Undetermined node"),
+        }
+    }
+    cache[&node]
+}
+
+fn witness<R>(dd: &BddManager, f: NodeId, g: NodeId, rel: &R) -> Option<Witness<bool>>
+where
+    R: Fn(&bool, &bool) -> bool,
+{
+    let mut path = Vec::new();
+    let mut checked = HashSet::new();
+    find_pair(&|x| step(dd, x), f, g, rel, &mut path, &mut checked).map(|values| Witness {
+        assignment: path.into_iter().collect(),
+        values,
+    })
+}
+
+// an assignment on which rel(f, g) fails; f and g may belong to different managers,
+// in which case both are copied into a temporary manager and the variables are matched by label
+pub fn compare<R>(dd1: &BddManager, f: NodeId, dd2: &BddManager, g: NodeId, rel: &R) -> Option<Witness<bool>>
+where
+    R: Fn(&bool, &bool) -> bool,
+{
+    if std::ptr::eq(dd1, dd2) {
+        return witness(dd1, f, g, rel);
+    }
+    let mut order = labels(dd1, f);
+    for x in labels(dd2, g) {
+        if !order.contains(&x) {
+            order.push(x);
+        }
+    }
+    let mut dd = BddManager::new();
+    let mut vars = HashMap::new();
+    for (level, x) in order.iter().enumerate() {
+        let h = dd.create_header(level, x);
+        let (x0, x1) = (dd.zero(), dd.one());
+        let node = dd.create_node(h, x0, x1);
+        vars.insert(x.clone(), node);
+    }
+    let f = copy(dd1, f, &mut dd, &vars, &mut BddHashMap::default());
+    let g = copy(dd2, g, &mut dd, &vars, &mut BddHashMap::default());
+    witness(&dd, f, g, rel)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bss::BddMgr;
+
+    #[test]
+    fn test_equivalent() {
+        let mut bss1 = BddMgr::new();
+        let f = bss1.rpn("a b & c |").unwrap();
+        // the same function with the other order of variables
+        let mut bss2 = BddMgr::new();
+        let g = bss2.rpn("c b a & |").unwrap();
+        assert!(f.equivalent(&g).unwrap().is_none());
+        assert!(g.equivalent(&f).unwrap().is_none());
+        let h = bss2.rpn("c a |").unwrap();
+        assert!(f.implies(&h).unwrap().is_none());
+        let w = h.implies(&f).unwrap().unwrap();
+        println!("{:?}", w);
+        assert_eq!(w.assignment["a"], 1);
+        assert_eq!(w.assignment["c"], 0);
+        assert_eq!(w.values, (true, false));
+        let w = f.equivalent(&h).unwrap().unwrap();
+        assert_eq!(w.values, (false, true));
+        // in the same manager
+        let k = bss1.rpn("c b a & |").unwrap();
+        assert!(f.equivalent(&k).unwrap().is_none());
+        assert!(k.implies(&bss1.rpn("a c |").unwrap()).unwrap().is_none());
+    }
+}
@@ -0,0 +1,188 @@
+use crate::prelude::*;
+use crate::bss::BddNode;
+
+#[derive(Debug, Clone)]
+pub struct Sequence {
+    pub name: String,
+    // Some(true): the functional event fails, Some(false): it succeeds, None: it is not asked
+    pub branches: Vec<Option<bool>>,
+    pub end_state: String,
+}
+
+// event tree whose functional events are fault trees in the same BddMgr
+pub struct EventTree {
+    frequency: f64,
+    events: Vec<(String, BddNode)>,
+    sequences: Vec<Sequence>,
+}
+
+// value of the BDD when the variables in failed are true and the others are false
+pub fn eval(node: &BddNode, failed: &HashSet<String>) -> bool {
+    let mut node = node.clone();
+    while let Some(label) = node.get_label() {
+        let i = if failed.contains(&label) { 1 } else { 0 };
+        node = node.get_child(i).unwrap();
+    }
+    node.is_one()
+}
+
+impl EventTree {
+    // frequency of the initiating event
+    pub fn new(frequency: f64) -> Self {
+        EventTree {
+            frequency,
+            events: Vec::new(),
+            sequences: Vec::new(),
+        }
+    }
+
+    // functional event given by the fault tree of its failure
+    pub fn add_event(&mut self, name: &str, ft: &BddNode) {
+        self.events.push((name.to_string(), ft.clone()));
+    }
+
+    // sequence given by the branches (functional event, failed) in the order of the tree
+    pub fn add_sequence(&mut self, name: &str, branches: &[(&str, bool)], end_state: &str) -> Result<(), String> {
+        let mut result = vec![None; self.events.len()];
+        for &(event, failed) in branches.iter() {
+            match self.events.iter().position(|(x, _)| x == event) {
+                Some(i) => result[i] = Some(failed),
+                None => return Err(format!("Unknown functional event: {}", event)),
+            }
+        }
+        self.sequences.push(Sequence {
+            name: name.to_string(),
+            branches: result,
+            end_state: end_state.to_string(),
+        });
+        Ok(())
+    }
+
+    pub fn get_sequences(&self) -> &[Sequence] {
+        &self.sequences
+    }
+
+    fn one(&self) -> Result<BddNode, String> {
+        match self.events.first() {
+            Some((_, ft)) => {
+                let mgr = ft.get_mgr();
+                let node = mgr.borrow().one();
+                Ok(BddNode::new(&mgr, node))
+            }
+            None => Err("No functional event".to_string()),
+        }
+    }
+
+    // conjunction of the failed fault trees and the negations of the succeeded ones
+    fn sequence_node(&self, seq: &Sequence, coherent: bool) -> Result<BddNode, String> {
+        let mut result = self.one()?;
+        for (i, b) in seq.branches.iter().enumerate() {
+            match b {
+                Some(true) => result = result.and(&self.events[i].1),
+                Some(false) if !coherent => result = result.and(&self.events[i].1.not()),
+                _ => (),
+            }
+        }
+        Ok(result)
+    }
+
+    pub fn sequence(&self, name: &str) -> Result<BddNode, String> {
+        match self.sequences.iter().find(|x| x.name == name) {
+            Some(seq) => self.sequence_node(seq, false),
+            None => Err(format!("Unknown sequence: {}", name)),
+        }
+    }
+
+    // frequency of each sequence
+    pub fn frequencies(&self, pv: &HashMap<String, f64>) -> Result<Vec<(String, f64)>, String> {
+        let mut result = Vec::new();
+        for seq in self.sequences.iter() {
+            let p = self.sequence_node(seq, false)?.prob(pv, &[true]);
+            result.push((seq.name.clone(), self.frequency * p));
+        }
+        Ok(result)
+    }
+
+    pub fn end_states(&self) -> Vec<String> {
+        let mut result: Vec<String> = Vec::new();
+        for seq in self.sequences.iter() {
+            if !result.contains(&seq.end_state) {
+                result.push(seq.end_state.clone());
+            }
+        }
+        result
+    }
+
+    // disjunction of the sequences leading to the end state
+    pub fn end_state(&self, end_state: &str) -> Result<BddNode, String> {
+        self.end_state_node(end_state, false)
+    }
+
+    fn end_state_node(&self, end_state: &str, coherent: bool) -> Result<BddNode, String> {
+        let mut result = self.one()?.not();
+        for seq in self.sequences.iter().filter(|x| x.end_state == end_state) {
+            result = result.or(&self.sequence_node(seq, coherent)?);
+        }
+        Ok(result)
+    }
+
+    pub fn end_state_frequency(&self, end_state: &str, pv: &HashMap<String, f64>) -> Result<f64, String> {
+        Ok(self.frequency * self.end_state(end_state)?.prob(pv, &[true]))
+    }
+
+    // minimal cut sets of the end state; the cut sets of the failed branches are computed
+    // and those contradicting the succeeded branches are deleted
+    pub fn cut_sets(&self, end_state: &str) -> Result<Vec<Vec<String>>, String> {
+        let exact = self.end_state_node(end_state, false)?;
+        let coherent = self.end_state_node(end_state, true)?;
+        let result = coherent
+            .minpath_unchecked()
+            .zdd_extract(&[true])
+            .filter(|x| eval(&exact, &x.iter().cloned().collect()))
+            .collect();
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bss::BddMgr;
+
+    #[test]
+    fn test_event_tree() {
+        let mut bss = BddMgr::new();
+        let fe1 = bss.rpn("a b |").unwrap();
+        let fe2 = bss.rpn("b c |").unwrap();
+        let mut et = EventTree::new(1.0e-2);
+        et.add_event("FE1", &fe1);
+        et.add_event("FE2", &fe2);
+        et.add_sequence("S1", &[("FE1", false), ("FE2", false)], "OK").unwrap();
+        et.add_sequence("S2", &[("FE1", false), ("FE2", true)], "CD").unwrap();
+        et.add_sequence("S3", &[("FE1", true)], "CD").unwrap();
+        assert!(et.add_sequence("S4", &[("FE3", true)], "CD").is_err());
+        let mut pv = HashMap::new();
+        pv.insert("a".to_string(), 0.1);
+        pv.insert("b".to_string(), 0.2);
+        pv.insert("c".to_string(), 0.3);
+        let total: f64 = et.frequencies(&pv).unwrap().iter().map(|(_, f)| f).sum();
+        assert!((total - 1.0e-2).abs() < 1.0e-15);
+        let cd = 1.0 - 0.9 * 0.8 * 0.7;
+        assert!((et.end_state_frequency("CD", &pv).unwrap() - 1.0e-2 * cd).abs() < 1.0e-15);
+        assert_eq!(et.end_states(), vec!["OK".to_string(), "CD".to_string()]);
+        let mut cuts = et.cut_sets("CD").unwrap();
+        cuts.sort();
+        assert_eq!(cuts, vec![vec!["a".to_string()], vec!["b".to_string()], vec!["c".to_string()]]);
+        // an end state reached only when FE1 succeeds and FE2 fails; the cut set {b} of FE2
+        // contradicts the success of FE1 and is deleted
+        let mut et = EventTree::new(1.0e-2);
+        et.add_event("FE1", &fe1);
+        et.add_event("FE2", &fe2);
+        et.add_sequence("S1", &[("FE1", false), ("FE2", false)], "OK").unwrap();
+        et.add_sequence("S2", &[("FE1", false), ("FE2", true)], "LD").unwrap();
+        et.add_sequence("S3", &[("FE1", true)], "CD").unwrap();
+        assert_eq!(et.cut_sets("LD").unwrap(), vec![vec!["c".to_string()]]);
+        let ld = 0.9 * 0.8 * 0.3;
+        assert!((et.end_state_frequency("LD", &pv).unwrap() - 1.0e-2 * ld).abs() < 1.0e-15);
+    }
+}
@@ -1,213 +1,194 @@
 use crate::prelude::*;
 
+// one step of minsol: the result itself, or a node built from the results of the children
+pub(crate) enum SolStep {
+    Done(NodeId),
+    Build(HeaderId, Vec<SolChild>),
+}
+
+pub(crate) enum SolChild {
+    // sol(x)
+    Sol(NodeId),
+    // without(sol(x), g)
+    SolWithout(NodeId, NodeId),
+    // without(f, sol(x))
+    WithoutSol(NodeId, NodeId),
+}
+
+// one step of without: the result itself, the result of another pair,
+// or a node built from the results of the pairs
+pub(crate) enum WithoutStep {
+    Done(NodeId),
+    Alias(NodeId, NodeId),
+    Build(HeaderId, Vec<(NodeId, NodeId)>),
+}
+
+enum StackValue {
+    Sol(NodeId),
+    Without(NodeId, NodeId),
+    WithoutLeft(NodeId),
+    WithoutRight(NodeId),
+    BuildSol(NodeId, HeaderId, usize),
+    BuildWithout((NodeId, NodeId), HeaderId, usize),
+    Alias((NodeId, NodeId)),
+}
+
+// explicit-stack evaluation of minsol-like recursions; `sol` and `without` give one step
+// of each recursion and `create` makes a node from a header and the children
+pub(crate) fn solve<D, S, W, C>(
+    dd: &mut D,
+    node: NodeId,
+    cache1: &mut BddHashMap<NodeId, NodeId>,
+    cache2: &mut BddHashMap<(NodeId, NodeId), NodeId>,
+    sol: S,
+    without: W,
+    create: C,
+) -> NodeId
+where
+    S: Fn(&mut D, NodeId) -> SolStep,
+    W: Fn(&mut D, NodeId, NodeId) -> WithoutStep,
+    C: Fn(&mut D, HeaderId, &[NodeId]) -> NodeId,
+{
+    let mut next_stack = vec![StackValue::Sol(node)];
+    let mut result_stack: Vec<NodeId> = Vec::new();
+    while let Some(stackvalue) = next_stack.pop() {
+        match stackvalue {
+            StackValue::Sol(f) => {
+                if let Some(&x) = cache1.get(&f) {
+                    result_stack.push(x);
+                    continue;
+                }
+                match sol(dd, f) {
+                    SolStep::Done(x) => {
+                        cache1.insert(f, x);
+                        result_stack.push(x);
+                    }
+                    SolStep::Build(headerid, children) => {
+                        next_stack.push(StackValue::BuildSol(f, headerid, children.len()));
+                        for child in children.into_iter().rev() {
+                            match child {
+                                SolChild::Sol(x) => {
+                                    next_stack.push(StackValue::Sol(x));
+                                }
+                                SolChild::SolWithout(x, g) => {
+                                    next_stack.push(StackValue::WithoutLeft(g));
+                                    next_stack.push(StackValue::Sol(x));
+                                }
+                                SolChild::WithoutSol(x, f) => {
+                                    next_stack.push(StackValue::WithoutRight(f));
+                                    next_stack.push(StackValue::Sol(x));
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            StackValue::Without(f, g) => {
+                let key = (f, g);
+                if let Some(&x) = cache2.get(&key) {
+                    result_stack.push(x);
+                    continue;
+                }
+                match without(dd, f, g) {
+                    WithoutStep::Done(x) => {
+                        cache2.insert(key, x);
+                        result_stack.push(x);
+                    }
+                    WithoutStep::Alias(f, g) => {
+                        next_stack.push(StackValue::Alias(key));
+                        next_stack.push(StackValue::Without(f, g));
+                    }
+                    WithoutStep::Build(headerid, pairs) => {
+                        next_stack.push(StackValue::BuildWithout(key, headerid, pairs.len()));
+                        for (f, g) in pairs.into_iter().rev() {
+                            next_stack.push(StackValue::Without(f, g));
+                        }
+                    }
+                }
+            }
+            StackValue::WithoutLeft(g) => {
+                let f = result_stack.pop().unwrap();
+                next_stack.push(StackValue::Without(f, g));
+            }
+            StackValue::WithoutRight(f) => {
+                let g = result_stack.pop().unwrap();
+                next_stack.push(StackValue::Without(f, g));
+            }
+            StackValue::BuildSol(f, headerid, n) => {
+                let children = result_stack.split_off(result_stack.len() - n);
+                let x = create(dd, headerid, &children);
+                cache1.insert(f, x);
+                result_stack.push(x);
+            }
+            StackValue::BuildWithout(key, headerid, n) => {
+                let children = result_stack.split_off(result_stack.len() - n);
+                let x = create(dd, headerid, &children);
+                cache2.insert(key, x);
+                result_stack.push(x);
+            }
+            StackValue::Alias(key) => {
+                let x = *result_stack.last().unwrap();
+                cache2.insert(key, x);
+            }
+        }
+    }
+    result_stack.pop().unwrap()
+}
+
 pub fn minsol(
     dd: &mut BddManager,
     node: NodeId,
     cache1: &mut BddHashMap<NodeId, NodeId>,
     cache2: &mut BddHashMap<(NodeId, NodeId), NodeId>,
 ) -> NodeId {
-    let key = node;
-    if let Some(x) = cache1.get(&key) {
-        return x.clone();
-    }
-    let result = match dd.get_node(node).unwrap() {
-        bdd::Node::Zero => dd.zero(),
-        bdd::Node::One => dd.one(),
+    solve(
+        dd,
+        node,
+        cache1,
+        cache2,
+        minsol_step,
+        without_step,
+        |dd, headerid, x| dd.create_node(headerid, x[0], x[1]),
+    )
+}
+
+fn minsol_step(dd: &mut BddManager, node: NodeId) -> SolStep {
+    match dd.get_node(node).unwrap() {
+        bdd::Node::Zero => SolStep::Done(dd.zero()),
+        bdd::Node::One => SolStep::Done(dd.one()),
         bdd::Node::NonTerminal(fnode) => {
             let headerid = fnode.headerid();
             let f0 = fnode[0];
             let f1 = fnode[1];
-            let tmp = minsol(dd, f1, cache1, cache2);
-            let high = without(dd, tmp, f0, cache2);
-            let low = minsol(dd, f0, cache1, cache2);
-            dd.create_node(headerid, low, high)
+            SolStep::Build(headerid, vec![SolChild::Sol(f0), SolChild::SolWithout(f1, f0)])
         }
         bdd::Node::Undet => panic!("Undetermined node"),
-    };
-    cache1.insert(key, result);
-    result
+    }
 }
 
-// enum BddStackValue<'a, 'b> {
-//     Bdd2(&'a bdd::BddNode, &'a bdd::BddNode),
-//     BddHeader((NodeId, NodeId), &'b dd::nodes::NodeHeader),
-// }
-
-// pub fn minsol_stack(
-//     dd: &mut bdd::Bdd,
-//     node: &bdd::BddNode,
-//     cache1: &mut HashMap<NodeId, bdd::BddNode>,
-//     cache2: &mut HashMap<(NodeId, NodeId), bdd::BddNode>,
-// ) -> bdd::BddNode {
-//     let mut next_stack = Vec::with_capacity(2048);
-//     let mut result_stack = Vec::with_capacity(2048);
-//     next_stack.push(node);
-//     while let Some(x) = next_stack.pop() {
-//         if let Some(result) = cache1.get(&x.id()) {
-//             result_stack.push(result.clone());
-//             continue;
-//         }
-//         match x {
-//             bdd::BddNode::Zero => {
-//                 let result = dd.zero();
-//                 result_stack.push(result.clone());
-//             }
-//             bdd::BddNode::One => {
-//                 let result = dd.one();
-//                 result_stack.push(result.clone());
-//             }
-//             bdd::BddNode::NonTerminal(fnode) => {
-//                 next_stack.push(&fnode[0]);
-//                 next_stack.push(&fnode[1]);
-//                 next_stack.push(fnode);
-//             }
-//         }
-//     }
-//     let key = node.id();
-//     match cache1.get(&key) {
-//         Some(x) => x.clone(),
-//         None => {
-//             let result = match node {
-//                 bdd::BddNode::Zero => dd.zero(),
-//                 bdd::BddNode::One => dd.one(),
-//                 bdd::BddNode::NonTerminal(fnode) => {
-//                     let tmp = minsol(dd, &fnode[1], cache1, cache2);
-//                     let high = without_stack(dd, &tmp, &fnode[0], cache2);
-//                     let low = minsol(dd, &fnode[0], cache1, cache2);
-//                     dd.create_node(fnode.header(), &low, &high)
-//                 }
-//             };
-//             cache1.insert(key, result.clone());
-//             result
-//         }
-//     }
-// }
-
-// pub fn without_stack(
-//     dd: &mut bdd::Bdd,
-//     f: &bdd::BddNode, // minsol tree
-//     g: &bdd::BddNode,
-//     cache: &mut HashMap<(NodeId, NodeId), bdd::BddNode>,
-// ) -> bdd::BddNode {
-//     let mut next_stack = Vec::new(); //with_capacity(2048);
-//     let mut result_stack = Vec::new(); //with_capacity(2048);
-//     next_stack.push(BddStackValue::Bdd2(f, g));
-//     while let Some(stackvalue) = next_stack.pop() {
-//         match stackvalue {
-//             BddStackValue::BddHeader(key, header) => {
-//                 let high = result_stack.pop().unwrap();
-//                 let low = result_stack.pop().unwrap();
-//                 let result = dd.create_node(&header, &low, &high);
-//                 cache.insert(key, result.clone());
-//                 result_stack.push(result.clone());
-//             }
-//             BddStackValue::Bdd2(f, g) => {
-//                 let key = (f.id(), g.id());
-//                 if let Some(x) = cache.get(&key) {
-//                     result_stack.push(x.clone());
-//                     continue;
-//                 }
-//                 match (f, g) {
-//                     (bdd::BddNode::Zero, _) => {
-//                         let result = dd.zero();
-//                         cache.insert(key, result.clone());
-//                         result_stack.push(result.clone());
-//                     }
-//                     (_, bdd::BddNode::Zero) => {
-//                         let result = f;
-//                         cache.insert(key, result.clone());
-//                         result_stack.push(result.clone());
-//                     }
-//                     (_, bdd::BddNode::One) => {
-//                         let result = dd.zero();
-//                         cache.insert(key, result.clone());
-//                         result_stack.push(result.clone());
-//                     }
-//                     (bdd::BddNode::One, bdd::BddNode::NonTerminal(gnode)) => {
-//                         next_stack.push(BddStackValue::BddHeader(key, gnode.header()));
-//                         next_stack.push(BddStackValue::Bdd2(f, &gnode[1]));
-//                         next_stack.push(BddStackValue::Bdd2(f, &gnode[0]));
-//                     }
-//                     (bdd::BddNode::NonTerminal(fnode), bdd::BddNode::NonTerminal(gnode))
-//                         if fnode.id() == gnode.id() =>
-//                     {
-//                         let result = dd.zero();
-//                         cache.insert(key, result.clone());
-//                         result_stack.push(result.clone());
-//                     }
-//                     (bdd::BddNode::NonTerminal(fnode), bdd::BddNode::NonTerminal(gnode))
-//                         if fnode.level() > gnode.level() =>
-//                     {
-//                         next_stack.push(BddStackValue::BddHeader(key, fnode.header()));
-//                         next_stack.push(BddStackValue::Bdd2(&fnode[1], g));
-//                         next_stack.push(BddStackValue::Bdd2(&fnode[0], g));
-//                     }
-//                     (bdd::BddNode::NonTerminal(fnode), bdd::BddNode::NonTerminal(gnode))
-//                         if fnode.level() < gnode.level() =>
-//                     {
-//                         next_stack.push(BddStackValue::Bdd2(f, &gnode[0]));
-//                     }
-//                     (bdd::BddNode::NonTerminal(fnode), bdd::BddNode::NonTerminal(gnode)) => {
-//                         next_stack.push(BddStackValue::BddHeader(key, fnode.header()));
-//                         next_stack.push(BddStackValue::Bdd2(&fnode[1], &gnode[1]));
-//                         next_stack.push(BddStackValue::Bdd2(&fnode[0], &gnode[0]));
-//                     }
-//                 }
-//             }
-//         }
-//     }
-//     if let Some(node) = result_stack.pop() {
-//         node.clone()
-//     } else {
-//         panic!("result stack is empty");
-//     }
-// }
-
-fn without(
-    dd: &mut BddManager,
-    f: NodeId,
-    g: NodeId,
-    cache: &mut BddHashMap<(NodeId, NodeId), NodeId>,
-) -> NodeId {
-    let key = (f, g);
-    if let Some(x) = cache.get(&key) {
-        return x.clone();
-    }
-    let node = match (dd.get_node(f).unwrap(), dd.get_node(g).unwrap()) {
-        (bdd::Node::Zero, _) => dd.zero(),
-        (_, bdd::Node::Zero) => f,
-        (_, bdd::Node::One) => dd.zero(),
+fn without_step(dd: &mut BddManager, f: NodeId, g: NodeId) -> WithoutStep {
+    match (dd.get_node(f).unwrap(), dd.get_node(g).unwrap()) {
+        (bdd::Node::Zero, _) => WithoutStep::Done(dd.zero()),
+        (_, bdd::Node::Zero) => WithoutStep::Done(f),
+        (_, bdd::Node::One) => WithoutStep::Done(dd.zero()),
         (bdd::Node::One, bdd::Node::NonTerminal(gnode)) => {
             let headerid = gnode.headerid();
-            let gnodeid: Vec<_> = gnode.iter().cloned().collect();
-            let low = without(dd, f, gnodeid[0], cache);
-            let high = without(dd, f, gnodeid[1], cache);
-            dd.create_node(headerid, low, high)
+            WithoutStep::Build(headerid, vec![(f, gnode[0]), (f, gnode[1])])
         }
         (bdd::Node::NonTerminal(fnode), bdd::Node::NonTerminal(gnode)) if fnode.id() == gnode.id() => {
-            dd.zero()
+            WithoutStep::Done(dd.zero())
         }
         (bdd::Node::NonTerminal(fnode), bdd::Node::NonTerminal(_gnode)) if dd.level(f) > dd.level(g) => {
             let headerid = fnode.headerid();
-            let fnodeid: Vec<_> = fnode.iter().cloned().collect();
-            let low = without(dd, fnodeid[0], g, cache);
-            let high = without(dd, fnodeid[1], g, cache);
-            dd.create_node(headerid, low, high)
+            WithoutStep::Build(headerid, vec![(fnode[0], g), (fnode[1], g)])
         }
         (bdd::Node::NonTerminal(_fnode), bdd::Node::NonTerminal(gnode)) if dd.level(f) < dd.level(g) => {
-            without(dd, f, gnode[0], cache)
+            WithoutStep::Alias(f, gnode[0])
         }
         (bdd::Node::NonTerminal(fnode), bdd::Node::NonTerminal(gnode)) => {
             let headerid = fnode.headerid();
-            let fnodeid: Vec<_> = fnode.iter().cloned().collect();
-            let gnodeid: Vec<_> = gnode.iter().cloned().collect();
-            let low = without(dd, fnodeid[0], gnodeid[0], cache);
-            let high = without(dd, fnodeid[1], gnodeid[1], cache);
-            dd.create_node(headerid, low, high)
+            WithoutStep::Build(headerid, vec![(fnode[0], gnode[0]), (fnode[1], gnode[1])])
         }
         (bdd::Node::Undet, _) | (_, bdd::Node::Undet) => panic!("Undetermined node"),
-    };
-    cache.insert(key, node);
-    node
+    }
 }
-
@@ -0,0 +1,195 @@
+use crate::prelude::*;
+use std::cmp::Ordering;
+
+// one step of the traversal over a decision diagram for the monotonicity check
+pub enum Step<T> {
+    Leaf(Option<T>),
+    Inner(String, Level, Vec<NodeId>),
+}
+
+// a witness of non-monotonicity: raising var from lower[var] to upper[var] with the others
+// fixed decreases the value from values.0 to values.1. A variable missing in the assignments
+// may take any value
+#[derive(Debug, Clone)]
+pub struct Violation<T> {
+    pub var: String,
+    pub lower: HashMap<String, usize>,
+    pub upper: HashMap<String, usize>,
+    pub values: (T, T),
+}
+
+// check that the function is non-decreasing in every variable; for every node,
+// the i-th child should not exceed the (i+1)-th child
+pub fn check<T, F>(step: &F, root: NodeId) -> Result<(), Violation<T>>
+where
+    T: PartialOrd + Clone,
+    F: Fn(NodeId) -> Step<T>,
+{
+    let non_decreasing = |a: &T, b: &T| a.partial_cmp(b) != Some(Ordering::Greater);
+    let mut visited = BddHashSet::default();
+    let mut checked = HashSet::new();
+    // the edges from the root as (the previous edge, label, index) so that paths are not copied
+    let mut edges: Vec<(Option<usize>, String, usize)> = Vec::new();
+    let mut next_stack = vec![(root, None)];
+    while let Some((f, edge)) = next_stack.pop() {
+        if !visited.insert(f) {
+            continue;
+        }
+        if let Step::Inner(label, _, children) = step(f) {
+            for i in 0..children.len() - 1 {
+                let mut witness = Vec::new();
+                if let Some((a, b)) = find_pair(step, children[i], children[i + 1], &non_decreasing, &mut witness, &mut checked) {
+                    let mut e = edge;
+                    while let Some(k) = e {
+                        let (prev, x, j) = &edges[k];
+                        witness.push((x.clone(), *j));
+                        e = *prev;
+                    }
+                    let mut lower: HashMap<String, usize> = witness.into_iter().collect();
+                    let mut upper = lower.clone();
+                    lower.insert(label.clone(), i);
+                    upper.insert(label.clone(), i + 1);
+                    return Err(Violation {
+                        var: label,
+                        lower,
+                        upper,
+                        values: (a, b),
+                    });
+                }
+            }
+            for (i, x) in children.into_iter().enumerate() {
+                edges.push((edge, label.clone(), i));
+                next_stack.push((x, Some(edges.len() - 1)));
+            }
+        }
+    }
+    Ok(())
+}
+
+// the variables under the root as (level, label, a node of the variable) in ascending order of levels
+pub(crate) fn variables<T, F>(step: &F, root: NodeId) -> Vec<(Level, String, NodeId)>
+where
+    F: Fn(NodeId) -> Step<T>,
+{
+    let mut result = Vec::new();
+    let mut visited = BddHashSet::default();
+    let mut next_stack = vec![root];
+    while let Some(f) = next_stack.pop() {
+        if !visited.insert(f) {
+            continue;
+        }
+        if let Step::Inner(label, level, children) = step(f) {
+            result.push((level, label, f));
+            next_stack.extend(children);
+        }
+    }
+    result.sort();
+    result.dedup_by_key(|x| x.0);
+    result
+}
+
+enum StackValue {
+    Visit(NodeId, NodeId, usize, Option<(String, usize)>),
+    Checked(NodeId, NodeId),
+}
+
+// an assignment where rel(the value of f, the value of g) fails, appended to path;
+// the pairs without such an assignment are kept in checked
+pub(crate) fn find_pair<T, F, R>(
+    step: &F,
+    f: NodeId,
+    g: NodeId,
+    rel: &R,
+    path: &mut Vec<(String, usize)>,
+    checked: &mut HashSet<(NodeId, NodeId)>,
+) -> Option<(T, T)>
+where
+    F: Fn(NodeId) -> Step<T>,
+    R: Fn(&T, &T) -> bool,
+{
+    let mut next_stack = vec![StackValue::Visit(f, g, path.len(), None)];
+    while let Some(stackvalue) = next_stack.pop() {
+        match stackvalue {
+            StackValue::Visit(f, g, depth, edge) => {
+                path.truncate(depth);
+                if let Some(e) = edge {
+                    path.push(e);
+                }
+                if f == g || checked.contains(&(f, g)) {
+                    continue;
+                }
+                let (label, fchildren, gchildren) = match (step(f), step(g)) {
+                    (Step::Leaf(Some(a)), Step::Leaf(Some(b))) if !rel(&a, &b) => return Some((a, b)),
+                    (Step::Leaf(_), Step::Leaf(_)) => {
+                        checked.insert((f, g));
+                        continue;
+                    }
+                    (Step::Inner(label, flevel, fchildren), Step::Inner(_, glevel, gchildren)) if flevel == glevel => {
+                        (label, fchildren, gchildren)
+                    }
+                    (Step::Inner(label, flevel, fchildren), Step::Inner(_, glevel, _)) if flevel > glevel => {
+                        let n = fchildren.len();
+                        (label, fchildren, vec![g; n])
+                    }
+                    (Step::Inner(_, _, _), Step::Inner(label, _, gchildren)) => {
+                        let n = gchildren.len();
+                        (label, vec![f; n], gchildren)
+                    }
+                    (Step::Inner(label, _, fchildren), Step::Leaf(_)) => {
+                        let n = fchildren.len();
+                        (label, fchildren, vec![g; n])
+                    }
+                    (Step::Leaf(_), Step::Inner(label, _, gchildren)) => {
+                        let n = gchildren.len();
+                        (label, vec![f; n], gchildren)
+                    }
+                };
+                next_stack.push(StackValue::Checked(f, g));
+                let depth = path.len();
+                for (i, (&f0, &g0)) in fchildren.iter().zip(gchildren.iter()).enumerate().rev() {
+                    next_stack.push(StackValue::Visit(f0, g0, depth, Some((label.clone(), i))));
+                }
+            }
+            StackValue::Checked(f, g) => {
+                checked.insert((f, g));
+            }
+        }
+    }
+    None
+}
+
+pub fn is_monotone(dd: &BddManager, node: NodeId) -> Result<(), Violation<bool>> {
+    let step = |x: NodeId| match dd.get_node(x).unwrap() {
+        bdd::Node::NonTerminal(fnode) => Step::Inner(
+            dd.label(x).unwrap().to_string(),
+            dd.level(x).unwrap(),
+            vec![fnode[0], fnode[1]],
+        ),
+        bdd::Node::Zero => Step::Leaf(Some(false)),
+        bdd::Node::One => Step::Leaf(Some(true)),
+        bdd::Node::Undet => Step::Leaf(None),
+    };
+    check(&step, node)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bss::BddMgr;
+
+    #[test]
+    fn test_is_monotone() {
+        let mut bss = BddMgr::new();
+        let f = bss.rpn("a b & c |").unwrap();
+        assert!(f.is_monotone().is_ok());
+        assert!(f.minpath().is_ok());
+        let g = bss.rpn("a b ~ & c |").unwrap();
+        let v = g.is_monotone().unwrap_err();
+        println!("{:?}", v);
+        assert_eq!(v.var, "b");
+        assert_eq!(v.lower["a"], 1);
+        assert_eq!(v.upper["a"], 1);
+        assert_eq!(v.lower["c"], 0);
+        assert_eq!(v.values, (true, false));
+        assert!(g.minpath().is_err());
+    }
+}
@@ -0,0 +1,418 @@
+use crate::prelude::*;
+
+use std::collections::BTreeSet;
+
+// reachability among the vertices kept in the frontier: (a, b) means a reaches b
+type Reach = BTreeSet<(usize, usize)>;
+
+struct StNetwork<'a> {
+    edges: &'a [(usize, usize, NodeId)],
+    s: usize,
+    t: usize,
+    directed: bool,
+    last: HashMap<usize, usize>,
+}
+
+impl<'a> StNetwork<'a> {
+    fn new(edges: &'a [(usize, usize, NodeId)], s: usize, t: usize, directed: bool) -> Self {
+        let mut last = HashMap::new();
+        for (i, &(u, v, _)) in edges.iter().enumerate() {
+            last.insert(u, i);
+            last.insert(v, i);
+        }
+        StNetwork {
+            edges,
+            s,
+            t,
+            directed,
+            last,
+        }
+    }
+
+    // true if the vertex has an edge whose index is i or later
+    fn is_frontier(&self, w: usize, i: usize) -> bool {
+        match self.last.get(&w) {
+            Some(&k) => k >= i,
+            None => false,
+        }
+    }
+
+    fn add_edge(&self, state: &Reach, u: usize, v: usize) -> Reach {
+        let mut result = state.clone();
+        let mut connect = |x: usize, y: usize| {
+            let sources: Vec<_> = state
+                .iter()
+                .filter(|&&(_, b)| b == x)
+                .map(|&(a, _)| a)
+                .chain(std::iter::once(x))
+                .collect();
+            let targets: Vec<_> = state
+                .iter()
+                .filter(|&&(a, _)| a == y)
+                .map(|&(_, b)| b)
+                .chain(std::iter::once(y))
+                .collect();
+            for &a in sources.iter() {
+                for &b in targets.iter() {
+                    if a != b {
+                        result.insert((a, b));
+                    }
+                }
+            }
+        };
+        connect(u, v);
+        if !self.directed {
+            connect(v, u);
+        }
+        result
+    }
+
+    fn build(
+        &self,
+        dd: &mut BddManager,
+        i: usize,
+        state: Reach,
+        cache: &mut HashMap<(usize, Reach), NodeId>,
+    ) -> NodeId {
+        if state.contains(&(self.s, self.t)) {
+            return dd.one();
+        }
+        // drop the vertices that no longer have edges
+        let state: Reach = state
+            .into_iter()
+            .filter(|&(a, b)| {
+                (a == self.s || a == self.t || self.is_frontier(a, i))
+                    && (b == self.s || b == self.t || self.is_frontier(b, i))
+            })
+            .collect();
+        // s cannot reach t if no vertex reachable from s has remaining edges
+        let alive = self.is_frontier(self.s, i)
+            || state
+                .iter()
+                .any(|&(a, b)| a == self.s && self.is_frontier(b, i));
+        if i == self.edges.len() || !alive {
+            return dd.zero();
+        }
+        let key = (i, state);
+        if let Some(&x) = cache.get(&key) {
+            return x;
+        }
+        let (u, v, x) = self.edges[i];
+        let low = self.build(dd, i + 1, key.1.clone(), cache);
+        let high = self.build(dd, i + 1, self.add_edge(&key.1, u, v), cache);
+        let result = dd.ite(x, high, low);
+        cache.insert(key, result);
+        result
+    }
+}
+
+// two-terminal connectivity by frontier-based construction;
+// each edge is (u, v, var) where var is the node of the variable that is true when the edge works
+pub fn network_st(
+    dd: &mut BddManager,
+    edges: &[(usize, usize, NodeId)],
+    s: usize,
+    t: usize,
+    directed: bool,
+) -> NodeId {
+    if s == t {
+        return dd.one();
+    }
+    let net = StNetwork::new(edges, s, t, directed);
+    let mut cache = HashMap::new();
+    net.build(dd, 0, Reach::new(), &mut cache)
+}
+
+// reorder edges by the breadth-first order of vertices from s,
+// which keeps the frontier small for grid-like networks
+pub fn bfs_edge_order<E: Clone>(edges: &[(usize, usize, E)], s: usize) -> Vec<(usize, usize, E)> {
+    let mut adj: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (u, v, _) in edges.iter() {
+        adj.entry(*u).or_default().push(*v);
+        adj.entry(*v).or_default().push(*u);
+    }
+    let mut order = HashMap::new();
+    let mut queue = VecDeque::new();
+    order.insert(s, 0);
+    queue.push_back(s);
+    while let Some(u) = queue.pop_front() {
+        if let Some(vs) = adj.get(&u) {
+            for &v in vs.iter() {
+                if !order.contains_key(&v) {
+                    order.insert(v, order.len());
+                    queue.push_back(v);
+                }
+            }
+        }
+    }
+    let rank = |x: &usize| *order.get(x).unwrap_or(&usize::MAX);
+    let mut result = edges.to_vec();
+    result.sort_by_key(|(u, v, _)| {
+        let (a, b) = (rank(u), rank(v));
+        (a.min(b), a.max(b))
+    });
+    result
+}
+
+#[derive(Debug, Clone)]
+pub enum KtStep<E> {
+    // a vertex enters the frontier; the variable is true when the vertex works
+    Enter(usize, Option<E>),
+    Edge(usize, usize, E),
+    Leave(usize),
+}
+
+// the sequence of steps processed by the frontier-based construction of K-terminal connectivity
+pub fn kt_steps<E: Clone>(
+    edges: &[(usize, usize, E)],
+    nodes: &HashMap<usize, E>,
+    terminals: &[usize],
+) -> Vec<KtStep<E>> {
+    let mut last = HashMap::new();
+    for (i, (u, v, _)) in edges.iter().enumerate() {
+        last.insert(*u, i);
+        last.insert(*v, i);
+    }
+    let mut result = Vec::new();
+    // vertices without edges
+    let mut isolated: Vec<usize> = terminals
+        .iter()
+        .chain(nodes.keys())
+        .filter(|w| !last.contains_key(*w))
+        .cloned()
+        .collect();
+    isolated.sort();
+    isolated.dedup();
+    for w in isolated.into_iter() {
+        result.push(KtStep::Enter(w, nodes.get(&w).cloned()));
+        result.push(KtStep::Leave(w));
+    }
+    let mut entered = HashSet::new();
+    for (i, (u, v, x)) in edges.iter().enumerate() {
+        let ends = if u == v { vec![*u] } else { vec![*u, *v] };
+        for &w in ends.iter() {
+            if entered.insert(w) {
+                result.push(KtStep::Enter(w, nodes.get(&w).cloned()));
+            }
+        }
+        result.push(KtStep::Edge(*u, *v, x.clone()));
+        for &w in ends.iter() {
+            if last.get(&w) == Some(&i) {
+                result.push(KtStep::Leave(w));
+            }
+        }
+    }
+    result
+}
+
+// connected components of the vertices in the frontier; a vertex in the frontier
+// that does not appear in comp has failed
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct KtState {
+    // (vertex, component) sorted by vertex
+    comp: Vec<(usize, usize)>,
+    // the number of terminals in each component
+    kcount: Vec<usize>,
+}
+
+impl KtState {
+    fn new() -> Self {
+        KtState {
+            comp: Vec::new(),
+            kcount: Vec::new(),
+        }
+    }
+
+    fn find(&self, w: usize) -> Option<usize> {
+        self.comp.iter().find(|&&(x, _)| x == w).map(|&(_, c)| c)
+    }
+
+    // renumber components in the order of their first vertices
+    fn normalize(&mut self) {
+        self.comp.sort();
+        let mut map = HashMap::new();
+        let mut kcount = Vec::new();
+        for (_, c) in self.comp.iter_mut() {
+            let n = map.len();
+            let k = *map.entry(*c).or_insert(n);
+            if k == kcount.len() {
+                kcount.push(self.kcount[*c]);
+            }
+            *c = k;
+        }
+        self.kcount = kcount;
+    }
+}
+
+struct KtNetwork<'a> {
+    steps: &'a [KtStep<NodeId>],
+    terminals: HashSet<usize>,
+}
+
+impl<'a> KtNetwork<'a> {
+    fn build(
+        &self,
+        dd: &mut BddManager,
+        i: usize,
+        state: KtState,
+        cache: &mut HashMap<(usize, KtState), NodeId>,
+    ) -> NodeId {
+        if i == self.steps.len() {
+            return dd.zero();
+        }
+        let key = (i, state);
+        if let Some(&x) = cache.get(&key) {
+            return x;
+        }
+        let state = &key.1;
+        let result = match &self.steps[i] {
+            KtStep::Enter(w, x) => {
+                let mut high = state.clone();
+                high.comp.push((*w, high.kcount.len()));
+                high.kcount.push(if self.terminals.contains(w) { 1 } else { 0 });
+                high.normalize();
+                let high = if high.kcount.iter().any(|&k| k == self.terminals.len()) {
+                    dd.one()
+                } else {
+                    self.build(dd, i + 1, high, cache)
+                };
+                match x {
+                    Some(x) => {
+                        let low = if self.terminals.contains(w) {
+                            dd.zero()
+                        } else {
+                            self.build(dd, i + 1, state.clone(), cache)
+                        };
+                        dd.ite(*x, high, low)
+                    }
+                    None => high,
+                }
+            }
+            KtStep::Edge(u, v, x) => {
+                match (state.find(*u), state.find(*v)) {
+                    (Some(cu), Some(cv)) if cu != cv => {
+                        let low = self.build(dd, i + 1, state.clone(), cache);
+                        let mut high = state.clone();
+                        for (_, c) in high.comp.iter_mut() {
+                            if *c == cv {
+                                *c = cu;
+                            }
+                        }
+                        high.kcount[cu] += high.kcount[cv];
+                        let high = if high.kcount[cu] == self.terminals.len() {
+                            dd.one()
+                        } else {
+                            high.normalize();
+                            self.build(dd, i + 1, high, cache)
+                        };
+                        dd.ite(*x, high, low)
+                    }
+                    // self-loops, edges inside a component, or edges of failed vertices
+                    _ => self.build(dd, i + 1, state.clone(), cache),
+                }
+            }
+            KtStep::Leave(w) => {
+                match state.find(*w) {
+                    Some(c) => {
+                        let mut next = state.clone();
+                        next.comp.retain(|&(x, _)| x != *w);
+                        let closed = next.comp.iter().all(|&(_, k)| k != c);
+                        if closed && state.kcount[c] > 0 {
+                            // the terminals in the closed component cannot reach the others
+                            dd.zero()
+                        } else {
+                            next.normalize();
+                            self.build(dd, i + 1, next, cache)
+                        }
+                    }
+                    // a failed vertex
+                    None => self.build(dd, i + 1, state.clone(), cache),
+                }
+            }
+        };
+        cache.insert(key, result);
+        result
+    }
+}
+
+// K-terminal connectivity by frontier-based construction with the steps given by kt_steps
+pub fn network_kt(dd: &mut BddManager, steps: &[KtStep<NodeId>], terminals: &[usize]) -> NodeId {
+    let terminals: HashSet<usize> = terminals.iter().cloned().collect();
+    if terminals.is_empty() {
+        return dd.one();
+    }
+    let net = KtNetwork { steps, terminals };
+    let mut cache = HashMap::new();
+    net.build(dd, 0, KtState::new(), &mut cache)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bss::BddMgr;
+
+    fn bridge() -> Vec<(usize, usize, &'static str)> {
+        vec![(0, 1, "a"), (0, 2, "b"), (1, 2, "c"), (1, 3, "d"), (2, 3, "e")]
+    }
+
+    #[test]
+    fn test_network_st() {
+        let mut bss = BddMgr::new();
+        let f = bss.network_st(&bridge(), 0, 3);
+        println!("{}", f.dot());
+        let mut pv = HashMap::new();
+        for x in ["a", "b", "c", "d", "e"] {
+            pv.insert(x.to_string(), 0.9);
+        }
+        let p: f64 = 0.9;
+        let exact = 2.0 * p.powi(2) + 2.0 * p.powi(3) - 5.0 * p.powi(4) + 2.0 * p.powi(5);
+        assert!((f.prob(&pv, &[true]) - exact).abs() < 1.0e-10);
+        let g = bss.rpn("a d & b e & | a c e & & | b c d & & |").unwrap();
+        assert!(f.eq(&g));
+        assert_eq!(f.minpath().unwrap().zdd_count(&[true]), 4);
+    }
+
+    #[test]
+    fn test_network_st_directed() {
+        let mut bss = BddMgr::new();
+        let f = bss.network_st_directed(&bridge(), 0, 3);
+        let g = bss.rpn("a d & b e & | a c e & & |").unwrap();
+        assert!(f.eq(&g));
+    }
+
+    #[test]
+    fn test_network_kt() {
+        let mut bss = BddMgr::new();
+        let f = bss.network_kt(&bridge(), &[0, 3]);
+        let g = bss.rpn("a d & b e & | a c e & & | b c d & & |").unwrap();
+        assert!(f.eq(&g));
+    }
+
+    #[test]
+    fn test_network_all() {
+        let mut bss = BddMgr::new();
+        let edges = vec![(0, 1, "a"), (1, 2, "b"), (0, 2, "c")];
+        let f = bss.network_all(&edges);
+        let g = bss.rpn("a b & b c & | a c & |").unwrap();
+        assert!(f.eq(&g));
+        assert_eq!(f.bdd_count(&[true]), 4);
+    }
+
+    #[test]
+    fn test_network_kt_nodes() {
+        let mut bss = BddMgr::new();
+        let f = bss.network_kt_nodes(&bridge(), &[(1, "n1"), (2, "n2")], &[0, 3]);
+        let g = bss
+            .rpn("a d & n1 & b e & n2 & | a c & e & n1 & n2 & | b c & d & n1 & n2 & |")
+            .unwrap();
+        assert!(f.eq(&g));
+    }
+
+    #[test]
+    fn test_bfs_edge_order() {
+        let edges = vec![(2, 3, "e"), (0, 1, "a"), (1, 3, "d"), (0, 2, "b")];
+        let result = bfs_edge_order(&edges, 0);
+        let labels: Vec<_> = result.iter().map(|(_, _, x)| *x).collect();
+        assert_eq!(labels, vec!["a", "b", "d", "e"]);
+    }
+}
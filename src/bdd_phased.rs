@@ -0,0 +1,142 @@
+use crate::prelude::*;
+use crate::bss::BddNode;
+
+// replace the variables in map by the given nodes
+pub fn compose(
+    dd: &mut BddManager,
+    node: NodeId,
+    map: &HashMap<String, NodeId>,
+    cache: &mut BddHashMap<NodeId, NodeId>,
+) -> NodeId {
+    let key = node;
+    if let Some(x) = cache.get(&key) {
+        return *x;
+    }
+    let result = match dd.get_node(node).unwrap() {
+        bdd::Node::Zero | bdd::Node::One | bdd::Node::Undet => node,
+        bdd::Node::NonTerminal(fnode) => {
+            let headerid = fnode.headerid();
+            let f0 = fnode[0];
+            let f1 = fnode[1];
+            let label = dd.label(node).unwrap().to_string();
+            let low = compose(dd, f0, map, cache);
+            let high = compose(dd, f1, map, cache);
+            let x = match map.get(&label) {
+                Some(&x) => x,
+                None => {
+                    let x0 = dd.zero();
+                    let x1 = dd.one();
+                    dd.create_node(headerid, x0, x1)
+                }
+            };
+            dd.ite(x, high, low)
+        }
+    };
+    cache.insert(key, result);
+    result
+}
+
+// phased-mission system of non-repairable components; the system failure is
+// checked at the end of each phase. The variable "c#j" is true when the component c
+// fails in the phase j given that it works at the beginning of the phase.
+pub struct PhasedMission {
+    failed: Vec<BddNode>,
+    components: Vec<String>,
+}
+
+impl PhasedMission {
+    pub fn new(failed: Vec<BddNode>, components: &[&str]) -> Self {
+        PhasedMission {
+            failed,
+            components: components.iter().map(|x| x.to_string()).collect(),
+        }
+    }
+
+    pub fn var_name(component: &str, phase: usize) -> String {
+        format!("{}#{}", component, phase + 1)
+    }
+
+    pub fn num_phases(&self) -> usize {
+        self.failed.len()
+    }
+
+    // the mission failure by the end of the phase
+    pub fn failure(&self, phase: usize) -> &BddNode {
+        &self.failed[phase]
+    }
+
+    // probabilities of the phase variables from q[c][j], the conditional failure probability
+    // of the component c in the phase j
+    pub fn pv(&self, q: &HashMap<String, Vec<f64>>) -> HashMap<String, f64> {
+        let mut result = HashMap::new();
+        for c in self.components.iter() {
+            if let Some(qs) = q.get(c) {
+                for (j, p) in qs.iter().enumerate() {
+                    result.insert(Self::var_name(c, j), *p);
+                }
+            }
+        }
+        result
+    }
+
+    // probabilities of the phase variables from the failure rates of components and the durations of phases
+    pub fn pv_exponential(&self, rates: &HashMap<String, Vec<f64>>, durations: &[f64]) -> HashMap<String, f64> {
+        let q: HashMap<String, Vec<f64>> = rates
+            .iter()
+            .map(|(c, r)| {
+                let qs: Vec<f64> = r
+                    .iter()
+                    .zip(durations.iter())
+                    .map(|(l, t)| 1.0 - (-l * t).exp())
+                    .collect();
+                (c.clone(), qs)
+            })
+            .collect();
+        self.pv(&q)
+    }
+
+    pub fn unreliability(&self, pv: &HashMap<String, f64>) -> f64 {
+        match self.failed.last() {
+            Some(f) => f.prob(pv, &[true]),
+            None => 0.0,
+        }
+    }
+
+    pub fn reliability(&self, pv: &HashMap<String, f64>) -> f64 {
+        1.0 - self.unreliability(pv)
+    }
+
+    // probabilities that the mission fails in each phase
+    pub fn phase_failure_probs(&self, pv: &HashMap<String, f64>) -> Vec<f64> {
+        let mut prev = 0.0;
+        let mut result = Vec::new();
+        for f in self.failed.iter() {
+            let p = f.prob(pv, &[true]);
+            result.push(p - prev);
+            prev = p;
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bss::BddMgr;
+
+    #[test]
+    fn test_phased_mission() {
+        let mut bss = BddMgr::new();
+        let f1 = bss.rpn("a b &").unwrap();
+        let f2 = bss.rpn("a b |").unwrap();
+        let pms = bss.phased_mission(&[f1, f2], &["a", "b"]);
+        let mut q = HashMap::new();
+        q.insert("a".to_string(), vec![0.1, 0.2]);
+        q.insert("b".to_string(), vec![0.3, 0.1]);
+        let pv = pms.pv(&q);
+        assert!((pms.reliability(&pv) - 0.9 * 0.8 * 0.7 * 0.9).abs() < 1.0e-12);
+        let probs = pms.phase_failure_probs(&pv);
+        assert!((probs[0] - 0.1 * 0.3).abs() < 1.0e-12);
+        assert!((probs[1] - (1.0 - 0.9 * 0.8 * 0.7 * 0.9 - 0.1 * 0.3)).abs() < 1.0e-12);
+    }
+}
@@ -10,36 +10,76 @@ pub fn prob<T>(
 where
     T: ProbValue,
 {
-    let key = node;
-    if let Some(x) = cache.get(&key) {
-        return x.clone();
-    }
-    let result = match dd.get_node(node).unwrap() {
-        bdd::Node::Zero => {
-            if ss.contains(&false) {
-                T::from(1.0)
-            } else {
-                T::from(0.0)
-            }
+    prob_given(dd, node, pv, ss, &HashMap::new(), cache)
+}
+
+// probability under evidence fixing some variables; the fixed variables are restricted
+// during the traversal instead of rebuilding the diagram
+pub fn prob_given<T>(
+    dd: &BddManager,
+    node: NodeId,
+    pv: &HashMap<String, T>,
+    ss: &[bool],
+    evidence: &HashMap<String, bool>,
+    cache: &mut BddHashMap<NodeId, T>,
+) -> T
+where
+    T: ProbValue,
+{
+    // post-order traversal with an explicit stack; a node is expanded first
+    // and evaluated when it is popped again after its children. A node of a fixed
+    // variable takes the value of the child selected by the evidence
+    let mut next_stack = vec![(node, false)];
+    while let Some((f, expanded)) = next_stack.pop() {
+        if cache.contains_key(&f) {
+            continue;
         }
-        bdd::Node::One => {
-            if ss.contains(&true) {
-                T::from(1.0)
-            } else {
-                T::from(0.0)
+        match dd.get_node(f).unwrap() {
+            bdd::Node::Zero => {
+                let result = if ss.contains(&false) {
+                    T::from(1.0)
+                } else {
+                    T::from(0.0)
+                };
+                cache.insert(f, result);
             }
+            bdd::Node::One => {
+                let result = if ss.contains(&true) {
+                    T::from(1.0)
+                } else {
+                    T::from(0.0)
+                };
+                cache.insert(f, result);
+            }
+            bdd::Node::NonTerminal(fnode) if expanded => {
+                let x = dd.label(f).unwrap();
+                let result = match evidence.get(x) {
+                    Some(&false) => *cache.get(&fnode[0]).unwrap(),
+                    Some(&true) => *cache.get(&fnode[1]).unwrap(),
+                    None => {
+                        let fp = *pv.get(x).unwrap_or(&T::from(0.0));
+                        let low = *cache.get(&fnode[0]).unwrap();
+                        let high = *cache.get(&fnode[1]).unwrap();
+                        (T::from(1.0) - fp) * low + fp * high
+                    }
+                };
+                cache.insert(f, result);
+            }
+            bdd::Node::NonTerminal(fnode) => {
+                next_stack.push((f, true));
+                match evidence.get(dd.label(f).unwrap()) {
+                    Some(&false) => next_stack.push((fnode[0], false)),
+                    Some(&true) => next_stack.push((fnode[1], false)),
+                    None => {
+                        next_stack.push((fnode[1], false));
+                        next_stack.push((fnode[0], false));
+                    }
+                }
+            }
+            bdd::Node::Undet => panic!("Undetermined node"),
         }
-        bdd::Node::NonTerminal(fnode) => {
-            let x = dd.label(node).unwrap();
-            let fp = *pv.get(x).unwrap_or(&T::from(0.0));
-            let low = prob(dd, fnode[0], pv, ss, cache);
-            let high = prob(dd, fnode[1], pv, ss, cache);
-            (T::from(1.0) - fp) * low + fp * high
-        }
-        bdd::Node::Undet => panic!("Undetermined node"),
-    };
-    cache.insert(key, result);
-    result
+    }
+    *cache.get(&node).unwrap()
 }
 
 pub fn bmeas<T>(
@@ -90,53 +130,116 @@ where
     gradevent
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum CheckedState {
-    Persistent,
-    Temporary,
+// P(x = 1 | f in ss) for every variable in env; with B = P(f|x=1) - P(f|x=0),
+// P(x = 1, f in ss) = p * (P + (1 - p) * B)
+pub fn posterior<T>(
+    dd: &BddManager,
+    ss: &[bool],
+    node: NodeId,
+    env: &HashMap<String, T>,
+) -> Result<HashMap<String, T>, String>
+where
+    T: ProbValue + Div<Output = T>,
+{
+    let mut cache = BddHashMap::default();
+    let total = prob(dd, node, env, ss, &mut cache);
+    if total == T::from(0.0) {
+        return Err("The condition has probability zero".to_string());
+    }
+    let grad = bmeas(dd, ss, node, env);
+    let mut result = HashMap::new();
+    for (x, &p) in env.iter() {
+        let b = *grad.get(x).unwrap_or(&T::from(0.0));
+        result.insert(x.clone(), p * (total + (T::from(1.0) - p) * b) / total);
+    }
+    Ok(result)
 }
 
-fn topological_sort(dd: &BddManager, f: NodeId) -> Vec<NodeId> {
-    let mut result = Vec::new();
-    let mut check = BddHashMap::default();
-    let mut queue = VecDeque::new();
-    queue.push_back(f.clone());
-    while let Some(node) = queue.pop_front() {
-        match check.get(&node) {
-            Some(&CheckedState::Temporary) => panic!("DAG has a closed path"),
-            Some(&CheckedState::Persistent) => (),
-            None => {
-                visit(dd, node, &mut check, &mut result, &mut queue);
+// probabilities for many parameter sets in one pass; cols[level][k] is the probability of
+// the variable at the level in the k-th parameter set. The nodes are evaluated in topological
+// order and the values of each node are stored in a contiguous block of the buffer
+pub fn prob_batch(
+    dd: &BddManager,
+    node: NodeId,
+    cols: &[Vec<f64>],
+    m: usize,
+    ss: &[bool],
+) -> Result<Vec<f64>, String> {
+    let mut sorted_nodes = topological_sort(dd, node);
+    sorted_nodes.reverse();
+    let mut index = BddHashMap::default();
+    let mut buffer = vec![0.0; sorted_nodes.len() * m];
+    for (k, &f) in sorted_nodes.iter().enumerate() {
+        index.insert(f, k);
+        let (done, rest) = buffer.split_at_mut(k * m);
+        let row = &mut rest[..m];
+        match dd.get_node(f).unwrap() {
+            bdd::Node::Zero => row.fill(if ss.contains(&false) { 1.0 } else { 0.0 }),
+            bdd::Node::One => row.fill(if ss.contains(&true) { 1.0 } else { 0.0 }),
+            bdd::Node::NonTerminal(fnode) => {
+                let level = dd.level(f).unwrap();
+                let p = match cols.get(level) {
+                    Some(x) if x.len() == m => x,
+                    _ => return Err(format!("No parameters of length {} for level {}", m, level)),
+                };
+                let i0 = *index.get(&fnode[0]).unwrap();
+                let i1 = *index.get(&fnode[1]).unwrap();
+                let low = &done[i0 * m..(i0 + 1) * m];
+                let high = &done[i1 * m..(i1 + 1) * m];
+                for (((r, &q), &x0), &x1) in row.iter_mut().zip(p.iter()).zip(low.iter()).zip(high.iter()) {
+                    *r = (1.0 - q) * x0 + q * x1;
+                }
             }
+            bdd::Node::Undet => panic!("Undetermined node"),
         }
     }
-    result.reverse();
-    result
+    let root = sorted_nodes.len() - 1;
+    Ok(buffer.split_off(root * m))
 }
 
-fn visit(
+// the same as prob_batch for the parameter sets given as rows
+pub fn prob_batch_rows(
     dd: &BddManager,
-    x: NodeId,
-    check: &mut BddHashMap<NodeId, CheckedState>,
-    result: &mut Vec<NodeId>,
-    queue: &mut VecDeque<NodeId>,
-) {
-    match check.get(&x) {
-        Some(&CheckedState::Temporary) => panic!("DAG has a closed path"),
-        Some(&CheckedState::Persistent) => (),
-        None => {
-            check.insert(x, CheckedState::Temporary);
-            match dd.get_node(x).unwrap() {
-                bdd::Node::Zero | bdd::Node::One | bdd::Node::Undet => (),
-                bdd::Node::NonTerminal(fnode) => {
-                    for &m in fnode.iter() {
-                        queue.push_back(m);
-                        visit(dd, m, check, result, queue);
-                    }
-                }
+    node: NodeId,
+    pvs: &[HashMap<String, f64>],
+    ss: &[bool],
+) -> Result<Vec<f64>, String> {
+    let mut cols: Vec<Vec<f64>> = Vec::new();
+    for f in topological_sort(dd, node) {
+        if let (Some(level), Some(x)) = (dd.level(f), dd.label(f)) {
+            if cols.len() <= level {
+                cols.resize(level + 1, Vec::new());
+            }
+            if cols[level].is_empty() {
+                cols[level] = pvs.iter().map(|pv| *pv.get(x).unwrap_or(&0.0)).collect();
             }
-            check.insert(x, CheckedState::Persistent);
+        }
+    }
+    prob_batch(dd, node, &cols, pvs.len(), ss)
+}
+
+// nodes in the order that parents come before children
+fn topological_sort(dd: &BddManager, f: NodeId) -> Vec<NodeId> {
+    let mut result = Vec::new();
+    let mut visited = BddHashSet::default();
+    let mut next_stack = vec![(f, false)];
+    while let Some((x, done)) = next_stack.pop() {
+        if done {
             result.push(x);
+            continue;
+        }
+        if !visited.insert(x) {
+            continue;
+        }
+        next_stack.push((x, true));
+        if let bdd::Node::NonTerminal(fnode) = dd.get_node(x).unwrap() {
+            for &m in fnode.iter() {
+                if !visited.contains(&m) {
+                    next_stack.push((m, false));
+                }
+            }
         }
     }
+    result.reverse();
+    result
 }
@@ -0,0 +1,705 @@
+use std::env;
+use std::fs;
+use std::io::Read;
+use std::process;
+
+use rust_mss::prelude::*;
+
+const USAGE: &str = "usage: mss <command> <model file or -> [--format json|csv]
+
+commands:
+  prob               probability that the value is in ss
+  bmeas              Birnbaum importance (BDD) or the gradient of prob (MDD)
+  minpath, cutsets   minimal path (cut) sets or vectors
+  count              the number of paths to ss
+  paths              all the paths to ss
+  dot                the diagram in the dot format
+  size               the numbers of nodes, terminals and edges
+
+model file (# starts a comment):
+  type bdd|mdd       the kind of the model (default: bdd)
+  order x y ...      variable order; the first one gets the lowest level
+  states x 3         the number of states of an MDD variable
+  prob x 0.1         the probability of x (BDD) or of the states of x (MDD)
+  ss 1 ...           the values of interest (default: 1)
+  rpn x y & z |      the expression in reverse Polish notation
+  infix (x & y) | z  the expression in infix notation;
+                     & | ~ and && || ! are the same for both types";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Default)]
+struct Model {
+    mdd: bool,
+    expr: Vec<String>,
+    order: Vec<String>,
+    states: HashMap<String, usize>,
+    probs: HashMap<String, Vec<f64>>,
+    ss: Vec<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Cell {
+    Num(String),
+    Str(String),
+    Empty,
+}
+
+enum Output {
+    Record(Vec<(String, Cell)>),
+    Table(Vec<String>, Vec<Vec<Cell>>),
+    Sets(Vec<Vec<String>>),
+    Text(String),
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.is_empty() || args[0] == "-h" || args[0] == "--help" {
+        println!("{}", USAGE);
+        return;
+    }
+    let result = parse_args(&args).and_then(|(command, path, format)| {
+        let text = read_model(&path)?;
+        let model = parse_model(&text)?;
+        let output = run(&model, &command)?;
+        Ok(render(&output, format))
+    });
+    match result {
+        Ok(s) => print!("{}", s),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            eprintln!("{}", USAGE);
+            process::exit(1);
+        }
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<(String, String, Format), String> {
+    let mut positional = Vec::new();
+    let mut format = Format::Json;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--format" | "-f" => {
+                format = match iter.next().map(|x| x.as_str()) {
+                    Some("json") => Format::Json,
+                    Some("csv") => Format::Csv,
+                    _ => return Err("The format should be json or csv".to_string()),
+                }
+            }
+            _ => positional.push(arg.clone()),
+        }
+    }
+    match positional.as_slice() {
+        [command, path] => Ok((command.clone(), path.clone(), format)),
+        _ => Err("A command and a model file are required".to_string()),
+    }
+}
+
+fn read_model(path: &str) -> Result<String, String> {
+    if path == "-" {
+        let mut text = String::new();
+        std::io::stdin()
+            .read_to_string(&mut text)
+            .map_err(|e| format!("stdin: {}", e))?;
+        Ok(text)
+    } else {
+        fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))
+    }
+}
+
+fn parse_model(text: &str) -> Result<Model, String> {
+    let mut model = Model::default();
+    for (n, line) in text.lines().enumerate() {
+        let line = match line.find('#') {
+            Some(i) => &line[..i],
+            None => line,
+        }
+        .trim();
+        if line.is_empty() {
+            continue;
+        }
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let rest = &tokens[1..];
+        match tokens[0] {
+            "type" => match rest {
+                ["bdd"] => model.mdd = false,
+                ["mdd"] => model.mdd = true,
+                _ => return Err(format!("line {}: the type should be bdd or mdd", n + 1)),
+            },
+            "order" => model.order.extend(rest.iter().map(|x| x.to_string())),
+            "states" => match rest {
+                [x, k] => {
+                    let k = k
+                        .parse::<usize>()
+                        .map_err(|_| format!("line {}: invalid number of states {}", n + 1, k))?;
+                    model.states.insert(x.to_string(), k);
+                }
+                _ => return Err(format!("line {}: states needs a variable and a number", n + 1)),
+            },
+            "prob" if rest.len() >= 2 => {
+                let values = rest[1..]
+                    .iter()
+                    .map(|x| x.parse::<f64>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| format!("line {}: invalid probability", n + 1))?;
+                model.probs.insert(rest[0].to_string(), values);
+            }
+            "prob" => return Err(format!("line {}: prob needs a variable and probabilities", n + 1)),
+            "ss" => {
+                let values = rest
+                    .iter()
+                    .map(|x| x.parse::<i64>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| format!("line {}: invalid value in ss", n + 1))?;
+                model.ss.extend(values);
+            }
+            "rpn" => model.expr = rest.iter().map(|x| x.to_string()).collect(),
+            "infix" => {
+                let expr = line["infix".len()..].trim();
+                model.expr = infix_to_rpn(expr).map_err(|e| format!("line {}: {}", n + 1, e))?;
+            }
+            x => return Err(format!("line {}: unknown keyword {}", n + 1, x)),
+        }
+    }
+    if model.expr.is_empty() {
+        return Err("No expression is given".to_string());
+    }
+    Ok(model)
+}
+
+fn tokenize(expr: &str) -> Result<Vec<String>, String> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_' || c == '.';
+    let chars: Vec<char> = expr.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if is_word(c) {
+            let start = i;
+            while i < chars.len() && is_word(chars[i]) {
+                i += 1;
+            }
+            tokens.push(chars[start..i].iter().collect());
+        } else if i + 1 < chars.len()
+            && ["<=", ">=", "==", "!=", "&&", "||"].contains(&chars[i..i + 2].iter().collect::<String>().as_str())
+        {
+            tokens.push(chars[i..i + 2].iter().collect());
+            i += 2;
+        } else if "+-*/<>&|^~!(),".contains(c) {
+            tokens.push(c.to_string());
+            i += 1;
+        } else {
+            return Err(format!("Unexpected character {}", c));
+        }
+    }
+    Ok(tokens)
+}
+
+fn precedence(op: &str) -> Option<u32> {
+    match op {
+        "~" | "!" => Some(8),
+        "*" | "/" => Some(7),
+        "+" | "-" => Some(6),
+        "<" | "<=" | ">" | ">=" => Some(5),
+        "==" | "!=" => Some(4),
+        "&" | "&&" => Some(3),
+        "^" => Some(2),
+        "|" | "||" => Some(1),
+        _ => None,
+    }
+}
+
+// the shunting-yard algorithm; ~ and ! are prefix operators, and a word followed by
+// a parenthesis is a function such as min(x, y)
+fn infix_to_rpn(expr: &str) -> Result<Vec<String>, String> {
+    let tokens = tokenize(expr)?;
+    let mut output = Vec::new();
+    let mut stack: Vec<String> = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        let t = token.as_str();
+        if t == "(" {
+            stack.push(token.clone());
+        } else if t == ")" || t == "," {
+            loop {
+                match stack.last().map(|x| x.as_str()) {
+                    Some("(") => break,
+                    Some(_) => output.push(stack.pop().unwrap()),
+                    None => return Err("Unbalanced parentheses".to_string()),
+                }
+            }
+            if t == ")" {
+                stack.pop();
+                if let Some(top) = stack.last() {
+                    if precedence(top).is_none() && top != "(" {
+                        output.push(stack.pop().unwrap());
+                    }
+                }
+            }
+        } else if let Some(p) = precedence(t) {
+            if t != "~" && t != "!" {
+                while let Some(top) = stack.last() {
+                    match precedence(top) {
+                        Some(q) if q >= p => output.push(stack.pop().unwrap()),
+                        _ => break,
+                    }
+                }
+            }
+            stack.push(token.clone());
+        } else if tokens.get(i + 1).map(|x| x.as_str()) == Some("(") {
+            stack.push(token.clone());
+        } else {
+            output.push(token.clone());
+        }
+    }
+    while let Some(top) = stack.pop() {
+        if top == "(" {
+            return Err("Unbalanced parentheses".to_string());
+        }
+        output.push(top);
+    }
+    Ok(output)
+}
+
+// the number of operands of an operator in the RPN of each backend; None for an operand
+fn arity(token: &str, mdd: bool) -> Option<usize> {
+    match token {
+        "?" => Some(3),
+        "~" if !mdd => Some(1),
+        "&" | "|" | "^" if !mdd => Some(2),
+        "!" if mdd => Some(1),
+        "+" | "-" | "*" | "/" | "min" | "max" | "==" | "!=" | "<" | "<=" | ">" | ">=" | "&&" | "||" if mdd => {
+            Some(2)
+        }
+        _ => None,
+    }
+}
+
+// the logical operators are written as & | ~ in BDD models and && || ! in MDD models;
+// either spelling is accepted and translated into the one of the backend
+fn translate(expr: &[String], mdd: bool) -> Vec<String> {
+    expr.iter()
+        .map(|x| {
+            let t = match (x.as_str(), mdd) {
+                ("&", true) => "&&",
+                ("|", true) => "||",
+                ("~", true) => "!",
+                ("&&", false) => "&",
+                ("||", false) => "|",
+                ("!", false) => "~",
+                (t, _) => t,
+            };
+            t.to_string()
+        })
+        .collect()
+}
+
+// check the RPN before it is evaluated; every operator has enough operands, and
+// every operand is a constant or a variable accepted by is_var
+fn check_rpn<F>(expr: &[String], mdd: bool, is_var: F) -> Result<(), String>
+where
+    F: Fn(&str) -> bool,
+{
+    let kind = if mdd { "mdd" } else { "bdd" };
+    let mut depth = 0;
+    for token in expr.iter() {
+        let t = token.as_str();
+        if let Some(n) = arity(t, mdd) {
+            if depth < n {
+                return Err(format!("{} needs {} operands", t, n));
+            }
+            depth = depth + 1 - n;
+        } else if t.starts_with("save(") && t.ends_with(')') {
+            if depth == 0 {
+                return Err(format!("Nothing to save for {}", t));
+            }
+        } else if t.starts_with("load(") && t.ends_with(')') {
+            depth += 1;
+        } else if precedence(t).is_some() || arity(t, !mdd).is_some() {
+            return Err(format!("{} is not an operator of {} models", t, kind));
+        } else if is_var(t) {
+            depth += 1;
+        } else {
+            return Err(format!("{} is not a variable of the {} model", t, kind));
+        }
+    }
+    if depth != 1 {
+        return Err("The expression should give exactly one value".to_string());
+    }
+    Ok(())
+}
+
+fn float(x: f64) -> Cell {
+    if x.is_finite() {
+        Cell::Num(x.to_string())
+    } else {
+        Cell::Empty
+    }
+}
+
+fn run(model: &Model, command: &str) -> Result<Output, String> {
+    if model.mdd {
+        run_mdd(model, command)
+    } else {
+        run_bdd(model, command)
+    }
+}
+
+fn run_bdd(model: &Model, command: &str) -> Result<Output, String> {
+    let mut bss = BddMgr::new();
+    for x in model.order.iter() {
+        bss.defvar(x);
+    }
+    // a variable of a BDD model is defined when it first appears
+    let expr = translate(&model.expr, false);
+    check_rpn(&expr, false, |x| {
+        let numeric = x.starts_with(|c: char| c.is_ascii_digit() || c == '.');
+        !numeric || x == "0" || x == "1"
+    })?;
+    let f = bss.rpn(&expr.join(" "))?;
+    if let Some(x) = model.ss.iter().find(|&&x| x != 0 && x != 1) {
+        return Err(format!("The value {} in ss should be 0 or 1 for a BDD model", x));
+    }
+    let ss: Vec<bool> = if model.ss.is_empty() {
+        vec![true]
+    } else {
+        model.ss.iter().map(|&x| x == 1).collect()
+    };
+    let mut pv = HashMap::new();
+    for (k, v) in model.probs.iter() {
+        if v.len() != 1 {
+            return Err(format!("The probability of {} should be one value for a BDD model", k));
+        }
+        pv.insert(k.clone(), v[0]);
+    }
+    if command == "prob" || command == "bmeas" {
+        for x in bss.get_varorder() {
+            if !pv.contains_key(&x) {
+                return Err(format!("No probability is given for {}", x));
+            }
+        }
+    }
+    let output = match command {
+        "prob" => Output::Record(vec![("prob".to_string(), float(f.prob(&pv, &ss)))]),
+        "bmeas" => {
+            let result = f.bmeas(&pv, &ss);
+            let mut labels: Vec<&String> = result.keys().collect();
+            labels.sort();
+            let rows = labels
+                .into_iter()
+                .map(|x| vec![Cell::Str(x.clone()), float(result[x])])
+                .collect();
+            Output::Table(vec!["var".to_string(), "value".to_string()], rows)
+        }
+        "minpath" | "cutsets" => Output::Sets(f.minpath()?.zdd_extract(&[true]).collect()),
+        "count" => Output::Record(vec![("count".to_string(), Cell::Num(f.bdd_count(&ss).to_string()))]),
+        "paths" => Output::Sets(f.bdd_extract(&ss).collect()),
+        "dot" => Output::Text(f.dot()),
+        "size" => {
+            let (nn, nv, ne) = f.size();
+            size_record(nn, nv, ne)
+        }
+        _ => return Err(format!("Unknown command {}", command)),
+    };
+    Ok(output)
+}
+
+fn run_mdd(model: &Model, command: &str) -> Result<Output, String> {
+    let mut mgr: MddMgr<i64> = MddMgr::new();
+    let mut vars = model.states.clone();
+    for (k, v) in model.probs.iter() {
+        vars.entry(k.clone()).or_insert(v.len());
+    }
+    for x in model.order.iter() {
+        match vars.get(x) {
+            Some(&n) => {
+                mgr.defvar(x, n);
+            }
+            None => return Err(format!("The number of states of {} is not given", x)),
+        }
+    }
+    let expr = translate(&model.expr, true);
+    check_rpn(&expr, true, |x| {
+        x.parse::<i32>().is_ok() || x == "True" || x == "False" || vars.contains_key(x)
+    })?;
+    let mut f = mgr.rpn(&expr.join(" "), &vars)?;
+    let ss: Vec<i64> = if model.ss.is_empty() { vec![1] } else { model.ss.clone() };
+    let sset: HashSet<i64> = ss.iter().cloned().collect();
+    if command == "prob" || command == "bmeas" {
+        for (x, &n) in vars.iter() {
+            match model.probs.get(x) {
+                Some(v) if v.len() == n => (),
+                Some(_) => return Err(format!("The numbers of states and probabilities of {} are different", x)),
+                None => return Err(format!("No probability is given for {}", x)),
+            }
+        }
+    }
+    let output = match command {
+        "prob" => Output::Record(vec![("prob".to_string(), float(f.prob(&model.probs, &ss)))]),
+        "bmeas" => {
+            let result = f.grad(&model.probs, &ss);
+            let mut labels: Vec<&String> = result.keys().collect();
+            labels.sort();
+            let mut rows = Vec::new();
+            for x in labels {
+                for (i, &v) in result[x].iter().enumerate() {
+                    rows.push(vec![Cell::Str(x.clone()), Cell::Num(i.to_string()), float(v)]);
+                }
+            }
+            Output::Table(vec!["var".to_string(), "state".to_string(), "value".to_string()], rows)
+        }
+        // a variable missing in a minimal vector is at 0
+        "minpath" | "cutsets" => vectors(f.minpath()?.zmdd_extract(&sset).collect(), Cell::Num("0".to_string())),
+        "count" => Output::Record(vec![("count".to_string(), Cell::Num(f.mdd_count(&sset).to_string()))]),
+        "paths" => vectors(f.mdd_extract(&sset).collect(), Cell::Empty),
+        "dot" => Output::Text(f.dot()),
+        "size" => {
+            let (nn, nv, ne) = f.size();
+            size_record(nn, nv, ne)
+        }
+        _ => return Err(format!("Unknown command {}", command)),
+    };
+    Ok(output)
+}
+
+fn size_record(nn: u64, nv: u64, ne: u64) -> Output {
+    Output::Record(vec![
+        ("nodes".to_string(), Cell::Num(nn.to_string())),
+        ("terminals".to_string(), Cell::Num(nv.to_string())),
+        ("edges".to_string(), Cell::Num(ne.to_string())),
+    ])
+}
+
+// a table of vectors; the columns are the variables in alphabetical order
+fn vectors(paths: Vec<HashMap<String, usize>>, missing: Cell) -> Output {
+    let mut header: Vec<String> = paths
+        .iter()
+        .flat_map(|p| p.keys().cloned())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    header.sort();
+    let rows = paths
+        .iter()
+        .map(|p| {
+            header
+                .iter()
+                .map(|x| match p.get(x) {
+                    Some(v) => Cell::Num(v.to_string()),
+                    None => missing.clone(),
+                })
+                .collect()
+        })
+        .collect();
+    Output::Table(header, rows)
+}
+
+fn json_string(s: &str) -> String {
+    let mut result = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+fn json_cell(cell: &Cell) -> String {
+    match cell {
+        Cell::Num(x) => x.clone(),
+        Cell::Str(x) => json_string(x),
+        Cell::Empty => "null".to_string(),
+    }
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains(|c: char| c == ',' || c == '"' || c == '\n') {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+fn csv_cell(cell: &Cell) -> String {
+    match cell {
+        Cell::Num(x) => x.clone(),
+        Cell::Str(x) => csv_field(x),
+        Cell::Empty => String::new(),
+    }
+}
+
+// a JSON array of the given items, one item per line
+fn json_array(items: Vec<String>) -> String {
+    if items.is_empty() {
+        "[]\n".to_string()
+    } else {
+        format!("[\n  {}\n]\n", items.join(",\n  "))
+    }
+}
+
+fn render(output: &Output, format: Format) -> String {
+    let mut result = match (output, format) {
+        (Output::Text(s), _) => s.clone(),
+        (Output::Record(fields), Format::Json) => {
+            let items: Vec<String> = fields
+                .iter()
+                .map(|(k, v)| format!("{}: {}", json_string(k), json_cell(v)))
+                .collect();
+            format!("{{{}}}", items.join(", "))
+        }
+        (Output::Record(fields), Format::Csv) => {
+            let header: Vec<String> = fields.iter().map(|(k, _)| csv_field(k)).collect();
+            let values: Vec<String> = fields.iter().map(|(_, v)| csv_cell(v)).collect();
+            format!("{}\n{}", header.join(","), values.join(","))
+        }
+        (Output::Table(header, rows), Format::Json) => {
+            let items = rows
+                .iter()
+                .map(|row| {
+                    let fields: Vec<String> = header
+                        .iter()
+                        .zip(row.iter())
+                        .filter(|(_, v)| **v != Cell::Empty)
+                        .map(|(k, v)| format!("{}: {}", json_string(k), json_cell(v)))
+                        .collect();
+                    format!("{{{}}}", fields.join(", "))
+                })
+                .collect();
+            json_array(items)
+        }
+        (Output::Table(header, rows), Format::Csv) => {
+            let mut lines = vec![header.iter().map(|x| csv_field(x)).collect::<Vec<_>>().join(",")];
+            for row in rows.iter() {
+                lines.push(row.iter().map(csv_cell).collect::<Vec<_>>().join(","));
+            }
+            lines.join("\n")
+        }
+        (Output::Sets(sets), Format::Json) => {
+            let items = sets
+                .iter()
+                .map(|s| {
+                    let xs: Vec<String> = s.iter().map(|x| json_string(x)).collect();
+                    format!("[{}]", xs.join(", "))
+                })
+                .collect();
+            json_array(items)
+        }
+        (Output::Sets(sets), Format::Csv) => sets
+            .iter()
+            .map(|s| s.iter().map(|x| csv_field(x)).collect::<Vec<_>>().join(","))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    };
+    if !result.ends_with('\n') {
+        result.push('\n');
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prob_of(output: &Output) -> f64 {
+        match output {
+            Output::Record(fields) => match &fields[0].1 {
+                Cell::Num(x) => x.parse().unwrap(),
+                _ => panic!("not a number"),
+            },
+            _ => panic!("not a record"),
+        }
+    }
+
+    #[test]
+    fn test_infix_to_rpn() {
+        assert_eq!(infix_to_rpn("a & ~b | c").unwrap(), vec!["a", "b", "~", "&", "c", "|"]);
+        assert_eq!(infix_to_rpn("(a | b) & c").unwrap(), vec!["a", "b", "|", "c", "&"]);
+        assert_eq!(
+            infix_to_rpn("min(x, y) + 1 >= 2").unwrap(),
+            vec!["x", "y", "min", "1", "+", "2", ">="]
+        );
+        assert_eq!(infix_to_rpn("x - y - z").unwrap(), vec!["x", "y", "-", "z", "-"]);
+        assert!(infix_to_rpn("(a & b").is_err());
+        assert!(infix_to_rpn("a $ b").is_err());
+    }
+
+    #[test]
+    fn test_bdd_model() {
+        let text = "# a pump system\ntype bdd\norder a b c\ninfix (a & b) | c\nprob a 0.1\nprob b 0.2\nprob c 0.01\n";
+        let model = parse_model(text).unwrap();
+        let p = prob_of(&run(&model, "prob").unwrap());
+        assert!((p - (0.02 + 0.01 - 0.0002)).abs() < 1.0e-12);
+        let out = render(&run(&model, "cutsets").unwrap(), Format::Json);
+        println!("{}", out);
+        assert_eq!(out.lines().count(), 4);
+        let out = render(&run(&model, "bmeas").unwrap(), Format::Csv);
+        println!("{}", out);
+        assert!(out.starts_with("var,value\na,"));
+        let out = render(&run(&model, "size").unwrap(), Format::Json);
+        assert!(out.starts_with("{\"nodes\": 3"));
+        assert!(run(&model, "unknown").is_err());
+        assert!(parse_model("type bdd\nprob a 0.1\n").is_err());
+        // a BDD model takes one probability for each variable and the states 0 and 1
+        let model = parse_model("type bdd\ninfix a | b\nprob a 0.1 0.9\nprob b 0.2\n").unwrap();
+        assert!(run(&model, "prob").is_err());
+        let model = parse_model("type bdd\ninfix a | b\nprob a 0.1\nprob b 0.2\nss 2\n").unwrap();
+        assert!(run(&model, "prob").is_err());
+        let model = parse_model("type bdd\ninfix a | b\nprob a 0.1\nprob b 0.2\nss 0\n").unwrap();
+        assert!((prob_of(&run(&model, "prob").unwrap()) - 0.72).abs() < 1.0e-12);
+    }
+
+    #[test]
+    fn test_mdd_model() {
+        let text = "type mdd\nstates x 3\nstates y 3\nrpn x y + 3 >=\nprob x 0.2 0.3 0.5\nprob y 0.1 0.6 0.3\nss 1\n";
+        let model = parse_model(text).unwrap();
+        let p = prob_of(&run(&model, "prob").unwrap());
+        assert!((p - 0.54).abs() < 1.0e-12);
+        let out = render(&run(&model, "minpath").unwrap(), Format::Csv);
+        println!("{}", out);
+        let mut lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "x,y");
+        lines.sort();
+        assert_eq!(lines, vec!["1,2", "2,1", "x,y"]);
+        let out = render(&run(&model, "paths").unwrap(), Format::Json);
+        println!("{}", out);
+        assert!(run(&parse_model("type mdd\norder z\nrpn z\n").unwrap(), "prob").is_err());
+    }
+
+    #[test]
+    fn test_malformed_model() {
+        // too few operands
+        let model = parse_model("infix a &\n").unwrap();
+        assert!(run(&model, "cutsets").is_err());
+        let model = parse_model("rpn a b c &\n").unwrap();
+        assert!(run(&model, "cutsets").is_err());
+        // a variable of an MDD model needs its states
+        let model = parse_model("type mdd\nstates x 3\ninfix x + y >= 2\n").unwrap();
+        assert!(run(&model, "count").is_err());
+        // the operators of the other backend
+        assert!(run(&parse_model("infix a + b\n").unwrap(), "count").is_err());
+        assert!(run(&parse_model("infix min(a, b)\n").unwrap(), "count").is_err());
+        assert!(run(&parse_model("type mdd\nstates x 3\nrpn x x ^\n").unwrap(), "count").is_err());
+        // the logical operators are translated into those of the backend
+        let text = "type mdd\nstates x 3\nstates y 3\ninfix x >= 1 & y >= 2\nss 1\n";
+        let out = render(&run(&parse_model(text).unwrap(), "count").unwrap(), Format::Json);
+        assert_eq!(out, "{\"count\": 2}\n");
+        let text = "type mdd\nstates x 3\nstates y 3\ninfix ~(x >= 1) | y >= 2\nss 1\n";
+        let out = render(&run(&parse_model(text).unwrap(), "count").unwrap(), Format::Json);
+        assert_eq!(out, "{\"count\": 5}\n");
+        let out = render(&run(&parse_model("infix a && !b\n").unwrap(), "count").unwrap(), Format::Json);
+        assert_eq!(out, "{\"count\": 1}\n");
+    }
+}
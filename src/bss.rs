@@ -5,10 +5,25 @@ use crate::bdd_path::*;
 use crate::bdd_count;
 use crate::bdd_prob;
 use crate::bdd_minsol;
+use crate::bdd_network;
+use crate::bdd_ccf::CcfGroup;
+use crate::bdd_phased;
+use crate::bdd_phased::PhasedMission;
+use crate::bdd_bounds;
+use crate::bdd_bounds::Approx;
+use crate::bdd_diag;
+use crate::bdd_monotone;
+use crate::bdd_monotone::Violation;
+use crate::bdd_equiv;
+use crate::bdd_equiv::Witness;
+use crate::zss::{ZddMgr, ZddNode};
 
 pub struct BddMgr {
     bdd: Rc<RefCell<bdd::BddManager>>,
     vars: HashMap<String, NodeId>,
+    ccf: HashMap<String, NodeId>,
+    ccf_groups: Vec<CcfGroup>,
+    zdd: ZddMgr,
 }
 
 #[derive(Debug, Clone)]
@@ -32,6 +47,9 @@ impl BddMgr {
         BddMgr {
             bdd: Rc::new(RefCell::new(bdd::BddManager::new())),
             vars: HashMap::default(),
+            ccf: HashMap::default(),
+            ccf_groups: Vec::new(),
+            zdd: ZddMgr::new(),
         }
     }
 
@@ -58,6 +76,9 @@ impl BddMgr {
 
     // defvar
     pub fn defvar(&mut self, var: &str) -> BddNode {
+        if let Some(node) = self.ccf.get(var) {
+            return BddNode::new(&self.bdd, *node);
+        }
         if let Some(node) = self.vars.get(var) {
             return BddNode::new(&self.bdd, *node);
         } else {
@@ -68,10 +89,57 @@ impl BddMgr {
             let x1 = bdd.one();
             let node = bdd.create_node(h, x0, x1);
             self.vars.insert(var.to_string(), node);
+            // the ZDD variables follow the same order
+            self.zdd.defvar(var);
             BddNode::new(&self.bdd, node)
         }
     }
 
+    // define a CCF group; each member used after this is expanded into
+    // the OR of its independent event and the CCF events
+    pub fn defccf(&mut self, group: &CcfGroup) -> Result<(), String> {
+        for x in group.members().iter() {
+            if self.vars.contains_key(x) || self.ccf.contains_key(x) {
+                return Err(format!("{} is already defined", x));
+            }
+        }
+        let events = group.events();
+        for (name, _) in events.iter() {
+            self.defvar(name);
+        }
+        for x in group.members().iter() {
+            let mut node = self.zero();
+            for (name, xs) in events.iter() {
+                if xs.contains(x) {
+                    node = node.or(&self.defvar(name));
+                }
+            }
+            self.ccf.insert(x.clone(), node.node);
+        }
+        self.ccf_groups.push(group.clone());
+        Ok(())
+    }
+
+    // probabilities of the basic events added by CCF groups, merged into pv;
+    // an error when pv already gives the probability of a CCF event or a member
+    pub fn ccf_pv(&self, pv: &HashMap<String, f64>) -> Result<HashMap<String, f64>, String> {
+        let mut result = pv.clone();
+        for group in self.ccf_groups.iter() {
+            for x in group.members().iter() {
+                if pv.contains_key(x) {
+                    return Err(format!("{} is a member of the CCF group {} and has no probability", x, group.name()));
+                }
+            }
+            for (name, p) in group.probabilities().into_iter() {
+                if pv.contains_key(&name) {
+                    return Err(format!("The probability of the CCF event {} is given by its group", name));
+                }
+                result.insert(name, p);
+            }
+        }
+        Ok(result)
+    }
+
     pub fn get_varorder(&self) -> Vec<String> {
         let bdd = self.bdd.borrow();
         let mut result = vec!["?".to_string(); self.vars.len()];
@@ -84,6 +152,28 @@ impl BddMgr {
         result
     }
 
+    // the ZDD manager whose variables mirror the BDD variables
+    pub fn zdd(&mut self) -> &mut ZddMgr {
+        &mut self.zdd
+    }
+
+    // read a minimal solution diagram (the result of minpath) as a family of sets
+    pub fn family(&mut self, node: &BddNode) -> Result<ZddNode, String> {
+        self.zdd.from_bdd(node)
+    }
+
+    // the family consisting of the given sets of variables
+    pub fn family_sets(&mut self, sets: &[Vec<&str>]) -> Result<ZddNode, String> {
+        for s in sets.iter() {
+            for &x in s.iter() {
+                if !self.vars.contains_key(x) {
+                    return Err(format!("Unknown variable {}", x));
+                }
+            }
+        }
+        Ok(self.zdd.sets(sets))
+    }
+
     pub fn rpn(&mut self, expr: &str) -> Result<BddNode, String> {
         let mut stack = Vec::new();
         let mut cache = HashMap::new();
@@ -99,32 +189,32 @@ impl BddMgr {
                 }
                 "&" => {
                     let mut bdd = self.bdd.borrow_mut();
-                    let right = stack.pop().unwrap();
-                    let left = stack.pop().unwrap();
+                    let right = stack.pop().ok_or(format!("Too few operands for {}", token))?;
+                    let left = stack.pop().ok_or(format!("Too few operands for {}", token))?;
                     stack.push(bdd.and(left, right));
                 }
                 "|" => {
                     let mut bdd = self.bdd.borrow_mut();
-                    let right = stack.pop().unwrap();
-                    let left = stack.pop().unwrap();
+                    let right = stack.pop().ok_or(format!("Too few operands for {}", token))?;
+                    let left = stack.pop().ok_or(format!("Too few operands for {}", token))?;
                     stack.push(bdd.or(left, right));
                 }
                 "^" => {
                     let mut bdd = self.bdd.borrow_mut();
-                    let right = stack.pop().unwrap();
-                    let left = stack.pop().unwrap();
+                    let right = stack.pop().ok_or(format!("Too few operands for {}", token))?;
+                    let left = stack.pop().ok_or(format!("Too few operands for {}", token))?;
                     stack.push(bdd.xor(left, right));
                 }
                 "~" => {
                     let mut bdd = self.bdd.borrow_mut();
-                    let node = stack.pop().unwrap();
+                    let node = stack.pop().ok_or(format!("Too few operands for {}", token))?;
                     stack.push(bdd.not(node));
                 }
                 "?" => {
                     let mut bdd = self.bdd.borrow_mut();
-                    let else_ = stack.pop().unwrap();
-                    let then = stack.pop().unwrap();
-                    let cond = stack.pop().unwrap();
+                    let else_ = stack.pop().ok_or(format!("Too few operands for {}", token))?;
+                    let then = stack.pop().ok_or(format!("Too few operands for {}", token))?;
+                    let cond = stack.pop().ok_or(format!("Too few operands for {}", token))?;
                     stack.push(bdd.ite(cond, then, else_));
                 }
                 _ if token.starts_with("save(") && token.ends_with(")") => {
@@ -174,6 +264,170 @@ impl BddMgr {
         BddNode::new(&self.bdd, result)
     }
 
+    // the OR of the AND-terms of the given sets of variables, e.g., cut sets from other tools
+    pub fn from_sets(&mut self, sets: &[Vec<&str>]) -> BddNode {
+        let terms: Vec<Vec<NodeId>> = sets
+            .iter()
+            .map(|s| s.iter().map(|x| self.defvar(x).node).collect())
+            .collect();
+        let mut bdd = self.bdd.borrow_mut();
+        let mut result = bdd.zero();
+        for term in terms.iter() {
+            let mut tmp = bdd.one();
+            for &x in term.iter() {
+                tmp = bdd.and(tmp, x);
+            }
+            result = bdd.or(result, tmp);
+        }
+        BddNode::new(&self.bdd, result)
+    }
+
+    // the family of the given sets of variables; undefined variables are defined
+    pub fn from_sets_zdd(&mut self, sets: &[Vec<&str>]) -> ZddNode {
+        for s in sets.iter() {
+            for &x in s.iter() {
+                self.defvar(x);
+            }
+        }
+        self.zdd.sets(sets)
+    }
+
+    // the OR of the cubes; a literal is x or ~x as in the paths of bdd_extract
+    pub fn from_cubes(&mut self, cubes: &[Vec<&str>]) -> Result<BddNode, String> {
+        let mut terms = Vec::new();
+        for cube in cubes.iter() {
+            let mut term = Vec::new();
+            for &literal in cube.iter() {
+                let (x, positive) = match literal.strip_prefix('~') {
+                    Some(x) => (x, false),
+                    None => (literal, true),
+                };
+                if x.is_empty() || x.starts_with('~') {
+                    return Err(format!("Invalid literal {}", literal));
+                }
+                term.push((self.defvar(x).node, positive));
+            }
+            terms.push(term);
+        }
+        let mut bdd = self.bdd.borrow_mut();
+        let mut result = bdd.zero();
+        for term in terms.iter() {
+            let mut tmp = bdd.one();
+            for &(x, positive) in term.iter() {
+                let literal = if positive { x } else { bdd.not(x) };
+                tmp = bdd.and(tmp, literal);
+            }
+            result = bdd.or(result, tmp);
+        }
+        Ok(BddNode::new(&self.bdd, result))
+    }
+
+    // variables of edges; the first edge gets the highest level so that
+    // the variable order follows the order of edges
+    fn edge_vars(&mut self, edges: &[(usize, usize, &str)]) -> Vec<(usize, usize, NodeId)> {
+        for (_, _, x) in edges.iter().rev() {
+            self.defvar(x);
+        }
+        edges
+            .iter()
+            .map(|&(u, v, x)| (u, v, self.defvar(x).node))
+            .collect()
+    }
+
+    // s-t connectivity of an undirected network; each edge is (u, v, label)
+    pub fn network_st(&mut self, edges: &[(usize, usize, &str)], s: usize, t: usize) -> BddNode {
+        let edges = self.edge_vars(edges);
+        let mut bdd = self.bdd.borrow_mut();
+        let node = bdd_network::network_st(&mut bdd, &edges, s, t, false);
+        BddNode::new(&self.bdd, node)
+    }
+
+    // s-t connectivity of a directed network; each edge is (u, v, label) from u to v
+    pub fn network_st_directed(&mut self, edges: &[(usize, usize, &str)], s: usize, t: usize) -> BddNode {
+        let edges = self.edge_vars(edges);
+        let mut bdd = self.bdd.borrow_mut();
+        let node = bdd_network::network_st(&mut bdd, &edges, s, t, true);
+        BddNode::new(&self.bdd, node)
+    }
+
+    // K-terminal connectivity of an undirected network with perfect vertices
+    pub fn network_kt(&mut self, edges: &[(usize, usize, &str)], terminals: &[usize]) -> BddNode {
+        self.network_kt_nodes(edges, &[], terminals)
+    }
+
+    // all-terminal connectivity of an undirected network with perfect vertices
+    pub fn network_all(&mut self, edges: &[(usize, usize, &str)]) -> BddNode {
+        let mut terminals: Vec<usize> = edges.iter().flat_map(|&(u, v, _)| [u, v]).collect();
+        terminals.sort();
+        terminals.dedup();
+        self.network_kt_nodes(edges, &[], terminals.as_slice())
+    }
+
+    // K-terminal connectivity of an undirected network; each vertex in nodes is (vertex, label)
+    // whose variable is true when the vertex works, and the others are perfect
+    pub fn network_kt_nodes(
+        &mut self,
+        edges: &[(usize, usize, &str)],
+        nodes: &[(usize, &str)],
+        terminals: &[usize],
+    ) -> BddNode {
+        let nodes: HashMap<usize, &str> = nodes.iter().cloned().collect();
+        let steps = bdd_network::kt_steps(edges, &nodes, terminals);
+        let labels: Vec<&str> = steps
+            .iter()
+            .filter_map(|x| match x {
+                bdd_network::KtStep::Enter(_, Some(x)) => Some(*x),
+                bdd_network::KtStep::Edge(_, _, x) => Some(*x),
+                _ => None,
+            })
+            .collect();
+        for x in labels.iter().rev() {
+            self.defvar(x);
+        }
+        let steps: Vec<_> = steps
+            .into_iter()
+            .map(|x| match x {
+                bdd_network::KtStep::Enter(w, Some(x)) => bdd_network::KtStep::Enter(w, Some(self.defvar(x).node)),
+                bdd_network::KtStep::Enter(w, None) => bdd_network::KtStep::Enter(w, None),
+                bdd_network::KtStep::Edge(u, v, x) => bdd_network::KtStep::Edge(u, v, self.defvar(x).node),
+                bdd_network::KtStep::Leave(w) => bdd_network::KtStep::Leave(w),
+            })
+            .collect();
+        let mut bdd = self.bdd.borrow_mut();
+        let node = bdd_network::network_kt(&mut bdd, &steps, terminals);
+        BddNode::new(&self.bdd, node)
+    }
+
+    // phased-mission system from the failure functions of phases over the components;
+    // the phase variables of each component are adjacent and the later phases are upper
+    pub fn phased_mission(&mut self, phases: &[BddNode], components: &[&str]) -> PhasedMission {
+        for c in components.iter() {
+            for j in 0..phases.len() {
+                self.defvar(&PhasedMission::var_name(c, j));
+            }
+        }
+        let mut failed = Vec::new();
+        let mut total = self.zero();
+        for (j, f) in phases.iter().enumerate() {
+            let mut map = HashMap::new();
+            for c in components.iter() {
+                let mut x = self.zero();
+                for k in 0..=j {
+                    x = x.or(&self.defvar(&PhasedMission::var_name(c, k)));
+                }
+                map.insert(c.to_string(), x.node);
+            }
+            let node = {
+                let mut bdd = self.bdd.borrow_mut();
+                let mut cache = BddHashMap::default();
+                bdd_phased::compose(&mut bdd, f.node, &map, &mut cache)
+            };
+            total = total.or(&BddNode::new(&self.bdd, node));
+            failed.push(total.clone());
+        }
+        PhasedMission::new(failed, components)
+    }
+
     // pub fn cache_clear(&self) {
     //     self.bdd.borrow_mut().cache_clear();
     // }
@@ -295,6 +549,28 @@ impl BddNode {
         self.node == other.node
     }
 
+    // check that self implies other; other may belong to another manager, and an assignment
+    // with self true and other false is returned otherwise. The result has the same shape
+    // as MddNode::implies
+    pub fn implies(&self, other: &BddNode) -> Result<Option<Witness<bool>>, String> {
+        self.compare(other, |a, b| !*a || *b)
+    }
+
+    // check that self and other are the same function, matching the variables by label
+    pub fn equivalent(&self, other: &BddNode) -> Result<Option<Witness<bool>>, String> {
+        self.compare(other, |a, b| a == b)
+    }
+
+    fn compare<R>(&self, other: &BddNode, rel: R) -> Result<Option<Witness<bool>>, String>
+    where
+        R: Fn(&bool, &bool) -> bool,
+    {
+        let bdd1 = self.parent.upgrade().unwrap();
+        let bdd2 = other.parent.upgrade().unwrap();
+        let result = bdd_equiv::compare(&bdd1.borrow(), self.node, &bdd2.borrow(), other.node, &rel);
+        Ok(result)
+    }
+
     pub fn prob<T>(&self, pv: &HashMap<String, T>, ss: &[bool]) -> T
     where
         T: ProbValue,
@@ -310,6 +586,32 @@ impl BddNode {
         )
     }
 
+    // probabilities for many parameter sets in one pass over the diagram
+    pub fn prob_batch(&self, pvs: &[HashMap<String, f64>], ss: &[bool]) -> Result<Vec<f64>, String> {
+        let bdd = self.parent.upgrade().unwrap();
+        let result = bdd_prob::prob_batch_rows(&bdd.borrow(), self.node, pvs, ss);
+        result
+    }
+
+    // the same as prob_batch; cols[i] is the column of the i-th variable in get_varorder
+    pub fn prob_batch_columns(&self, cols: &[Vec<f64>], ss: &[bool]) -> Result<Vec<f64>, String> {
+        let bdd = self.parent.upgrade().unwrap();
+        let m = cols.first().map_or(0, |x| x.len());
+        let result = bdd_prob::prob_batch(&bdd.borrow(), self.node, cols, m, ss);
+        result
+    }
+
+    // probability given that the variables in `evidence` are fixed to the known states
+    pub fn prob_given<T>(&self, pv: &HashMap<String, T>, ss: &[bool], evidence: &HashMap<String, bool>) -> T
+    where
+        T: ProbValue,
+    {
+        let bdd = self.parent.upgrade().unwrap();
+        let mut cache = BddHashMap::default();
+        let result = bdd_prob::prob_given(&bdd.borrow(), self.node, pv, ss, evidence, &mut cache);
+        result
+    }
+
     pub fn bmeas<T>(&self, pv: &HashMap<String, T>, ss: &[bool]) -> HashMap<String, T>
     where
         T: ProbValue,
@@ -318,8 +620,33 @@ impl BddNode {
         bdd_prob::bmeas(&mut bdd.clone().borrow_mut(), ss, self.node, &pv)
     }
 
-    // obtain minimal path vectors (mpvs) of monotone BDD
-    pub fn minpath(&self) -> BddNode {
+    // P(x = 1 | the value is in ss) for every variable in pv
+    pub fn posterior<T>(&self, pv: &HashMap<String, T>, ss: &[bool]) -> Result<HashMap<String, T>, String>
+    where
+        T: ProbValue + Div<Output = T>,
+    {
+        let bdd = self.parent.upgrade().unwrap();
+        let result = bdd_prob::posterior(&bdd.borrow(), ss, self.node, pv);
+        result
+    }
+
+    // check that the BDD is monotone (coherent); a counterexample is returned otherwise
+    pub fn is_monotone(&self) -> Result<(), Violation<bool>> {
+        let bdd = self.parent.upgrade().unwrap();
+        let result = bdd_monotone::is_monotone(&bdd.borrow(), self.node);
+        result
+    }
+
+    // obtain minimal path vectors (mpvs) of monotone BDD; an error for non-monotone BDD
+    pub fn minpath(&self) -> Result<BddNode, String> {
+        if let Err(v) = self.is_monotone() {
+            return Err(format!("The BDD is not monotone in {}", v.var));
+        }
+        Ok(self.minpath_unchecked())
+    }
+
+    // minpath without the monotonicity check; the result is meaningless for non-monotone BDD
+    pub fn minpath_unchecked(&self) -> BddNode {
         let bdd = self.parent.upgrade().unwrap();
         let mut cache1 = BddHashMap::default();
         let mut cache2 = BddHashMap::default();
@@ -327,6 +654,39 @@ impl BddNode {
         BddNode::new(&bdd, result)
     }
 
+    // minimal path vectors of monotone BDD as a family in zmgr
+    pub fn minpath_zdd(&self, zmgr: &mut ZddMgr) -> Result<ZddNode, String> {
+        zmgr.from_bdd_minsol(self)
+    }
+
+    // the family of the assignments making the BDD true; each set consists of the true variables
+    pub fn to_zdd(&self, zmgr: &mut ZddMgr) -> Result<ZddNode, String> {
+        zmgr.from_bdd_function(self)
+    }
+
+    // prime implicants of a possibly non-coherent BDD as a family over the literals x and ~x
+    pub fn prime_implicants(&self, zmgr: &mut ZddMgr) -> Result<ZddNode, String> {
+        zmgr.prime_implicants(self)
+    }
+
+    // an irredundant subset of the prime implicants whose OR is still the BDD
+    pub fn prime_cover(&self, zmgr: &mut ZddMgr) -> Result<ZddNode, String> {
+        zmgr.prime_cover(self)
+    }
+
+    // the n most probable minimal cut sets given that the value is true,
+    // with the posterior probabilities that all the members are failed
+    pub fn top_explanations(&self, pv: &HashMap<String, f64>, n: usize) -> Result<Vec<(Vec<String>, f64)>, String> {
+        let total = self.prob(pv, &[true]);
+        if total == 0.0 {
+            return Err("The system state has probability zero".to_string());
+        }
+        let path = self.minpath()?;
+        let bdd = self.parent.upgrade().unwrap();
+        let result = bdd_diag::top_n(&bdd.borrow(), path.node, pv, n);
+        Ok(result.into_iter().map(|(s, p)| (s, p / total)).collect())
+    }
+
     pub fn bdd_count(&self, ss: &[bool]) -> u64 {
         let bdd = self.parent.upgrade().unwrap();
         let mut cache = BddHashMap::default();
@@ -347,6 +707,30 @@ impl BddNode {
         ZddPath::new(self.clone(), ss)
     }
 
+    // rare-event approximation from minimal cut sets
+    pub fn rare_event(&self, cuts: &[Vec<String>], pv: &HashMap<String, f64>) -> Approx {
+        Approx::new(bdd_bounds::rare_event(cuts, pv), self.prob(pv, &[true]))
+    }
+
+    // min-cut upper bound from minimal cut sets
+    pub fn mcub(&self, cuts: &[Vec<String>], pv: &HashMap<String, f64>) -> Approx {
+        Approx::new(bdd_bounds::mcub(cuts, pv), self.prob(pv, &[true]))
+    }
+
+    // lower and upper bounds of Esary-Proschan from minimal path sets and minimal cut sets
+    pub fn esary_proschan(&self, paths: &[Vec<String>], cuts: &[Vec<String>], pv: &HashMap<String, f64>) -> (Approx, Approx) {
+        let exact = self.prob(pv, &[true]);
+        (
+            Approx::new(bdd_bounds::ep_lower(paths, pv), exact),
+            Approx::new(bdd_bounds::mcub(cuts, pv), exact),
+        )
+    }
+
+    // inclusion-exclusion over minimal cut sets truncated after k terms
+    pub fn inclusion_exclusion(&self, cuts: &[Vec<String>], pv: &HashMap<String, f64>, k: usize) -> Approx {
+        Approx::new(bdd_bounds::inclusion_exclusion(cuts, pv, k), self.prob(pv, &[true]))
+    }
+
     pub fn size(&self) -> (u64, u64, u64) {
         let bddmgr = self.parent.upgrade().unwrap();
         let bdd = bddmgr.borrow();
@@ -396,6 +780,128 @@ mod tests {
         println!("{:?}", result);
     }
 
+    #[test]
+    fn test_bss_mgr_prob_given() {
+        let mut bss = BddMgr::new();
+        let x = bss.defvar("x");
+        let y = bss.defvar("y");
+        let z = bss.defvar("z");
+        let f = x.and(&y).or(&z);
+        let mut pv = HashMap::new();
+        pv.insert("x".to_string(), 0.2);
+        pv.insert("y".to_string(), 0.3);
+        pv.insert("z".to_string(), 0.6);
+        let mut evidence = HashMap::new();
+        evidence.insert("y".to_string(), true);
+        let result = f.prob_given(&pv, &[true], &evidence);
+        println!("{:?}", result);
+        assert!((result - (1.0 - 0.8 * 0.4)).abs() < 1.0e-12);
+        evidence.insert("z".to_string(), false);
+        let result = f.prob_given(&pv, &[true], &evidence);
+        assert!((result - 0.2).abs() < 1.0e-12);
+    }
+
+    #[test]
+    fn test_bss_mgr_prob_batch() {
+        let mut bss = BddMgr::new();
+        let f = bss.rpn("x y & z |").unwrap();
+        let mut pvs = Vec::new();
+        for k in 0..100 {
+            let mut pv = HashMap::new();
+            pv.insert("x".to_string(), 0.01 * k as f64);
+            pv.insert("y".to_string(), 0.3);
+            pv.insert("z".to_string(), 1.0 - 0.005 * k as f64);
+            pvs.push(pv);
+        }
+        let result = f.prob_batch(&pvs, &[true]).unwrap();
+        for (pv, r) in pvs.iter().zip(result.iter()) {
+            assert!((f.prob(pv, &[true]) - r).abs() < 1.0e-12);
+        }
+        let order = bss.get_varorder();
+        let cols: Vec<Vec<f64>> = order
+            .iter()
+            .map(|x| pvs.iter().map(|pv| pv[x]).collect())
+            .collect();
+        let result2 = f.prob_batch_columns(&cols, &[true]).unwrap();
+        assert_eq!(result, result2);
+        assert!(f.prob_batch_columns(&cols[..1], &[true]).is_err());
+    }
+
+    #[test]
+    fn test_bss_mgr_posterior() {
+        let mut bss = BddMgr::new();
+        let x = bss.defvar("x");
+        let y = bss.defvar("y");
+        let z = bss.defvar("z");
+        let f = x.and(&y).or(&z);
+        let mut pv = HashMap::new();
+        pv.insert("x".to_string(), 0.2);
+        pv.insert("y".to_string(), 0.3);
+        pv.insert("z".to_string(), 0.6);
+        pv.insert("w".to_string(), 0.1);
+        let post = f.posterior(&pv, &[true]).unwrap();
+        println!("{:?}", post);
+        assert!((post["x"] - 0.2 * 0.72 / 0.624).abs() < 1.0e-12);
+        assert!((post["z"] - 0.6 / 0.624).abs() < 1.0e-12);
+        assert!((post["w"] - 0.1).abs() < 1.0e-12);
+        let post = f.posterior(&pv, &[false]).unwrap();
+        assert!(post["z"].abs() < 1.0e-12);
+    }
+
+    #[test]
+    fn test_deep_chain() {
+        // deep enough to overflow the thread stack with recursive traversals
+        let n = 50000;
+        let mut bss = BddMgr::new();
+        let mut f = bss.one();
+        let mut pv = HashMap::new();
+        for i in 0..n {
+            let label = format!("x{}", i);
+            let h = bss.defvar(&label).get_header().unwrap();
+            f = bss.create_node(h, &bss.zero(), &f);
+            pv.insert(label, 0.99999);
+        }
+        let result = f.prob(&pv, &[true]);
+        assert!((result - 0.99999f64.powi(n)).abs() < 1.0e-10);
+        assert_eq!(f.bdd_count(&[true]), 1);
+        let g = f.minpath().unwrap();
+        assert_eq!(g.zdd_count(&[true]), 1);
+        assert_eq!(g.get_id(), f.get_id());
+        let family = bss.family(&g).unwrap();
+        assert_eq!(family.count(), 1);
+        let sets = family.extract();
+        assert_eq!(sets.len(), 1);
+        assert_eq!(sets[0].len(), n as usize);
+        // the set operations and the conversions go through all the levels
+        assert!(family.minimal().union(&family).eq(&family));
+        assert!(family.supersets_of(&family).eq(&family));
+        assert!(family.restrict_cardinality(n as usize - 1).is_empty());
+        assert!(f.minpath_zdd(bss.zdd()).unwrap().eq(&family));
+        assert!(f.to_zdd(bss.zdd()).unwrap().eq(&family));
+        assert_eq!(family.to_bdd(&mut bss).unwrap().get_id(), f.get_id());
+    }
+
+    #[test]
+    fn test_family() {
+        let mut bss = BddMgr::new();
+        let f = bss.rpn("pump_a pump_b & pump_b valve & | pump_a valve & | power | pump_a sensor & |").unwrap();
+        let cuts = bss.family(&f.minpath().unwrap()).unwrap();
+        assert_eq!(cuts.count(), 5);
+        // the cut sets containing pump_a with order <= 2
+        let a = bss.family_sets(&[vec!["pump_a"]]).unwrap();
+        let result = cuts.supersets_of(&a).restrict_cardinality(2);
+        println!("{:?}", result.extract());
+        assert_eq!(result.count(), 3);
+        let others = cuts.diff(&result);
+        assert_eq!(others.count(), 2);
+        assert!(others.union(&result).eq(&cuts));
+        let power = bss.family_sets(&[vec!["power"]]).unwrap();
+        assert!(cuts.intersect(&power).eq(&power));
+        assert!(bss.family_sets(&[vec!["unknown"]]).is_err());
+        let m = f.minpath_zdd(bss.zdd()).unwrap();
+        assert!(m.eq(&cuts));
+    }
+
     #[test]
     fn test_bss_mgr_rpn() {
         let mut bss = BddMgr::new();
@@ -470,6 +976,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_from_sets() {
+        let mut bss = BddMgr::new();
+        let f = bss.rpn("a b & b c & | a c & | d |").unwrap();
+        let cuts: Vec<Vec<String>> = f.minpath().unwrap().zdd_extract(&[true]).collect();
+        let sets: Vec<Vec<&str>> = cuts.iter().map(|s| s.iter().map(|x| x.as_str()).collect()).collect();
+        println!("{:?}", sets);
+        let g = bss.from_sets(&sets);
+        assert!(g.eq(&f));
+        let z = bss.from_sets_zdd(&sets);
+        assert!(z.eq(&bss.family(&f.minpath().unwrap()).unwrap()));
+        let paths: Vec<Vec<String>> = f.bdd_extract(&[true]).collect();
+        let cubes: Vec<Vec<&str>> = paths.iter().map(|s| s.iter().map(|x| x.as_str()).collect()).collect();
+        let h = bss.from_cubes(&cubes).unwrap();
+        assert!(h.eq(&f));
+        let e = bss.from_cubes(&[vec!["a", "~b"], vec!["e"]]).unwrap();
+        assert!(e.eq(&bss.rpn("a b ~ & e |").unwrap()));
+        assert!(bss.from_cubes(&[vec!["~"]]).is_err());
+    }
+
     #[test]
     fn test_node_count() {
         let mut bss = BddMgr::new();
@@ -4,11 +4,29 @@ pub mod bdd_path;
 pub mod bdd_minsol;
 pub mod bdd_prob;
 pub mod bdd_count;
+pub mod bdd_network;
+pub mod bdd_ccf;
+pub mod bdd_phased;
+pub mod bdd_eventtree;
+pub mod bdd_bounds;
+pub mod bdd_diag;
+pub mod bdd_monotone;
+pub mod bdd_equiv;
 pub mod bss;
 
 pub mod mdd_path;
 pub mod mdd_prob;
 pub mod mdd_count;
 pub mod mdd_minsol;
+pub mod mdd_dist;
+pub mod mdd_ugf;
+pub mod mdd_flow;
+pub mod mdd_markov;
+pub mod mdd_diag;
+pub mod mdd_monotone;
+pub mod mdd_equiv;
 pub mod mss;
 
+pub mod zdd;
+pub mod zss;
+
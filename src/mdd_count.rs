@@ -21,6 +21,7 @@ where
     }
 }
 
+// every visit counts an edge; the first visit of a node counts the node itself
 fn vmddnode_count<V, T>(
     dd: &mtmdd2::MtMdd2Manager<V>,
     node: NodeId,
@@ -29,30 +30,27 @@ where
     T: Add<Output = T> + Clone + From<u32>,
     V: MDDValue,
 {
-    let key = node;
-    if cache.contains(&key) {
-        return (T::from(0), T::from(0), T::from(1));
-    }
-    let result = match dd.mtmdd().get_node(node).unwrap() {
-        mtmdd::Node::Terminal(_) | mtmdd::Node::Undet => {
-            (T::from(0), T::from(1), T::from(1))
+    let mut nn = T::from(0);
+    let mut nv = T::from(0);
+    let mut ne = T::from(0);
+    let mut next_stack = vec![node];
+    while let Some(f) = next_stack.pop() {
+        ne = ne + T::from(1);
+        if !cache.insert(f) {
+            continue;
         }
-        mtmdd::Node::NonTerminal(fnode) => {
-            let mut nn = T::from(1);
-            let mut nv = T::from(0);
-            let mut ne = T::from(1);
-            let fnodeid: Vec<_> = fnode.iter().cloned().collect();
-            for x in fnodeid.into_iter() {
-                let (ntmp, vtmp, etmp) = vmddnode_count(dd, x, cache);
-                nn = nn + ntmp;
-                nv = nv + vtmp;
-                ne = ne + etmp;
+        match dd.mtmdd().get_node(f).unwrap() {
+            mtmdd::Node::Terminal(_) | mtmdd::Node::Undet => {
+                nv = nv + T::from(1);
+            }
+            mtmdd::Node::NonTerminal(fnode) => {
+                nn = nn + T::from(1);
+                let fnodeid: Vec<_> = fnode.iter().cloned().collect();
+                next_stack.extend(fnodeid.into_iter().rev());
             }
-            (nn, nv, ne)
         }
-    };
-    cache.insert(key);
-    result
+    }
+    (nn, nv, ne)
 }
 
 fn bmddnode_count<V, T>(
@@ -63,30 +61,27 @@ where
     T: Add<Output = T> + Clone + From<u32>,
     V: MDDValue,
 {
-    let key = node;
-    if cache.contains(&key) {
-        return (T::from(0), T::from(0), T::from(1));
-    }
-    let result = match dd.mdd().get_node(node).unwrap() {
-        mdd::Node::Zero | mdd::Node::One | mdd::Node::Undet => {
-            (T::from(0), T::from(1), T::from(1))
+    let mut nn = T::from(0);
+    let mut nv = T::from(0);
+    let mut ne = T::from(0);
+    let mut next_stack = vec![node];
+    while let Some(f) = next_stack.pop() {
+        ne = ne + T::from(1);
+        if !cache.insert(f) {
+            continue;
         }
-        mdd::Node::NonTerminal(fnode) => {
-            let mut nn = T::from(1);
-            let mut nv = T::from(0);
-            let mut ne = T::from(1);
-            let fnodeid: Vec<_> = fnode.iter().cloned().collect();
-            for x in fnodeid.into_iter() {
-                let (ntmp, vtmp, etmp) = bmddnode_count(dd, x, cache);
-                nn = nn + ntmp;
-                nv = nv + vtmp;
-                ne = ne + etmp;
+        match dd.mdd().get_node(f).unwrap() {
+            mdd::Node::Zero | mdd::Node::One | mdd::Node::Undet => {
+                nv = nv + T::from(1);
+            }
+            mdd::Node::NonTerminal(fnode) => {
+                nn = nn + T::from(1);
+                let fnodeid: Vec<_> = fnode.iter().cloned().collect();
+                next_stack.extend(fnodeid.into_iter().rev());
             }
-            (nn, nv, ne)
         }
-    };
-    cache.insert(key);
-    result
+    }
+    (nn, nv, ne)
 }
 
 pub fn mdd_count<V, T>(
@@ -144,36 +139,55 @@ where
     T: Add<Output = T> + Clone + From<u32> + Mul<Output = T>,
     V: MDDValue,
 {
-    let key = (node, level);
-    if let Some(x) = cache.get(&key) {
-        return x.clone();
-    }
-    let result = match mdd.get_node(node).unwrap() {
-        mtmdd::Node::NonTerminal(_) | mtmdd::Node::Terminal(_) if level > mdd.level(node) => {
-            let nedges = mdd.get_header(level2headers[level.unwrap()]).unwrap().edge_num() as u32;
-            T::from(nedges) * vmdd_count(mdd, node, ss, cache, level.and_then(|x| x.checked_sub(1)), level2headers)
+    // the key (node, level) means the node seen from the given level;
+    // a level above the node is skipped and multiplies the number of its edges
+    let mut next_stack = vec![((node, level), false)];
+    while let Some((key, expanded)) = next_stack.pop() {
+        if cache.contains_key(&key) {
+            continue;
         }
-        mtmdd::Node::NonTerminal(fnode) => {
-            let mut result = T::from(0);
-            let fnodeid: Vec<_> = fnode.iter().cloned().collect();
-            for x in fnodeid.into_iter() {
-                let tmp = vmdd_count(mdd, x, ss, cache, level.and_then(|x| x.checked_sub(1)), level2headers);
-                result = result + tmp;
+        let (f, level) = key;
+        let next_level = level.and_then(|x| x.checked_sub(1));
+        match mdd.get_node(f).unwrap() {
+            mtmdd::Node::NonTerminal(_) | mtmdd::Node::Terminal(_) if level > mdd.level(f) => {
+                if expanded {
+                    let nedges = mdd.get_header(level2headers[level.unwrap()]).unwrap().edge_num() as u32;
+                    let result = T::from(nedges) * cache.get(&(f, next_level)).unwrap().clone();
+                    cache.insert(key, result);
+                } else {
+                    next_stack.push((key, true));
+                    next_stack.push(((f, next_level), false));
+                }
             }
-            result
-        }
-        mtmdd::Node::Terminal(fnode) => {
-            let value = fnode.value();
-            if ss.contains(&value) {
-                T::from(1)
-            } else {
-                T::from(0)
+            mtmdd::Node::NonTerminal(fnode) => {
+                if expanded {
+                    let mut result = T::from(0);
+                    for &x in fnode.iter() {
+                        result = result + cache.get(&(x, next_level)).unwrap().clone();
+                    }
+                    cache.insert(key, result);
+                } else {
+                    next_stack.push((key, true));
+                    let fnodeid: Vec<_> = fnode.iter().cloned().collect();
+                    for &x in fnodeid.iter().rev() {
+                        next_stack.push(((x, next_level), false));
+                    }
+                }
+            }
+            mtmdd::Node::Terminal(fnode) => {
+                let value = fnode.value();
+                if ss.contains(&value) {
+                    cache.insert(key, T::from(1));
+                } else {
+                    cache.insert(key, T::from(0));
+                }
+            }
+            mtmdd::Node::Undet => {
+                cache.insert(key, T::from(0));
             }
         }
-        mtmdd::Node::Undet => T::from(0),
-    };
-    cache.insert(key, result.clone());
-    result
+    }
+    cache.get(&(node, level)).unwrap().clone()
 }
 
 fn bmdd_count<V, T>(
@@ -188,42 +202,60 @@ where
     T: Add<Output = T> + Clone + From<u32> + Mul<Output = T>,
     V: MDDValue,
 {
-    let key = node;
-    if let Some(x) = cache.get(&key) {
-        return x.clone();
-    }
-    let result = match mdd.get_node(node).unwrap() {
-        mdd::Node::NonTerminal(_) | mdd::Node::Zero | mdd::Node::One if level > mdd.level(node) => {
-            let nedges = mdd.get_header(level2headers[level.unwrap()]).unwrap().edge_num() as u32;
-            T::from(nedges) * bmdd_count(mdd, node, ss, cache, level.and_then(|x| x.checked_sub(1)), level2headers)
+    // the nodes are cached without the level; a skipped level overwrites the value of
+    // the node cached at the next level
+    let mut next_stack = vec![(node, level, false)];
+    while let Some((f, level, expanded)) = next_stack.pop() {
+        if !expanded && cache.contains_key(&f) {
+            continue;
         }
-        mdd::Node::NonTerminal(fnode) => {
-            let mut result = T::from(0);
-            let fnodeid: Vec<_> = fnode.iter().cloned().collect();
-            for x in fnodeid.into_iter() {
-                let tmp = bmdd_count(mdd, x, ss, cache, level.and_then(|x| x.checked_sub(1)), level2headers);
-                result = result + tmp;
+        let next_level = level.and_then(|x| x.checked_sub(1));
+        match mdd.get_node(f).unwrap() {
+            mdd::Node::NonTerminal(_) | mdd::Node::Zero | mdd::Node::One if level > mdd.level(f) => {
+                if expanded {
+                    let nedges = mdd.get_header(level2headers[level.unwrap()]).unwrap().edge_num() as u32;
+                    let result = T::from(nedges) * cache.get(&f).unwrap().clone();
+                    cache.insert(f, result);
+                } else {
+                    next_stack.push((f, level, true));
+                    next_stack.push((f, next_level, false));
+                }
             }
-            result
-        }
-        mdd::Node::Zero => {
-            if ss.contains(&V::from(0)) {
-                T::from(1)
-            } else {
-                T::from(0)
+            mdd::Node::NonTerminal(fnode) => {
+                if expanded {
+                    let mut result = T::from(0);
+                    for x in fnode.iter() {
+                        result = result + cache.get(x).unwrap().clone();
+                    }
+                    cache.insert(f, result);
+                } else {
+                    next_stack.push((f, level, true));
+                    let fnodeid: Vec<_> = fnode.iter().cloned().collect();
+                    for &x in fnodeid.iter().rev() {
+                        next_stack.push((x, next_level, false));
+                    }
+                }
             }
-        }
-        mdd::Node::One => {
-            if ss.contains(&V::from(1)) {
-                T::from(1)
-            } else {
-                T::from(0)
+            mdd::Node::Zero => {
+                if ss.contains(&V::from(0)) {
+                    cache.insert(f, T::from(1));
+                } else {
+                    cache.insert(f, T::from(0));
+                }
+            }
+            mdd::Node::One => {
+                if ss.contains(&V::from(1)) {
+                    cache.insert(f, T::from(1));
+                } else {
+                    cache.insert(f, T::from(0));
+                }
+            }
+            mdd::Node::Undet => {
+                cache.insert(f, T::from(0));
             }
         }
-        mdd::Node::Undet => T::from(0),
-    };
-    cache.insert(key, result.clone());
-    result
+    }
+    cache.get(&node).unwrap().clone()
 }
 
 pub fn zmdd_count<V, T>(
@@ -257,32 +289,40 @@ where
     T: Add<Output = T> + Clone + From<u32> + Mul<Output = T>,
     V: MDDValue,
 {
-    let key = node;
-    if let Some(x) = cache.get(&key) {
-        return x.clone();
-    }
-    let result = match mdd.get_node(node).unwrap() {
-        mtmdd::Node::Terminal(fnode) => {
-            let value = fnode.value();
-            if ss.contains(&value) {
-                T::from(1)
-            } else {
-                T::from(0)
-            }
+    let mut next_stack = vec![(node, false)];
+    while let Some((f, expanded)) = next_stack.pop() {
+        if cache.contains_key(&f) {
+            continue;
         }
-        mtmdd::Node::NonTerminal(fnode) => {
-            let mut result = T::from(0);
-            let fnodeid: Vec<_> = fnode.iter().cloned().collect();
-            for x in fnodeid.into_iter() {
-                let tmp = vzmdd_count(mdd, x, ss, cache);
-                result = result + tmp;
+        match mdd.get_node(f).unwrap() {
+            mtmdd::Node::Terminal(fnode) => {
+                let value = fnode.value();
+                if ss.contains(&value) {
+                    cache.insert(f, T::from(1));
+                } else {
+                    cache.insert(f, T::from(0));
+                }
+            }
+            mtmdd::Node::NonTerminal(fnode) if expanded => {
+                let mut result = T::from(0);
+                for x in fnode.iter() {
+                    result = result + cache.get(x).unwrap().clone();
+                }
+                cache.insert(f, result);
+            }
+            mtmdd::Node::NonTerminal(fnode) => {
+                next_stack.push((f, true));
+                let fnodeid: Vec<_> = fnode.iter().cloned().collect();
+                for &x in fnodeid.iter().rev() {
+                    next_stack.push((x, false));
+                }
+            }
+            mtmdd::Node::Undet => {
+                cache.insert(f, T::from(0));
             }
-            result
         }
-        mtmdd::Node::Undet => T::from(0),
-    };
-    cache.insert(key, result.clone());
-    result
+    }
+    cache.get(&node).unwrap().clone()
 }
 
 fn bzmdd_count<V, T>(
@@ -295,38 +335,46 @@ where
     T: Add<Output = T> + Clone + From<u32> + Mul<Output = T>,
     V: MDDValue,
 {
-    let key = node;
-    if let Some(x) = cache.get(&key) {
-        return x.clone();
-    }
-    let result = match mdd.get_node(node).unwrap() {
-        mdd::Node::Zero => {
-            if ss.contains(&V::from(0)) {
-                T::from(1)
-            } else {
-                T::from(0)
-            }
+    let mut next_stack = vec![(node, false)];
+    while let Some((f, expanded)) = next_stack.pop() {
+        if cache.contains_key(&f) {
+            continue;
         }
-        mdd::Node::One => {
-            if ss.contains(&V::from(1)) {
-                T::from(1)
-            } else {
-                T::from(0)
+        match mdd.get_node(f).unwrap() {
+            mdd::Node::Zero => {
+                if ss.contains(&V::from(0)) {
+                    cache.insert(f, T::from(1));
+                } else {
+                    cache.insert(f, T::from(0));
+                }
             }
-        }
-        mdd::Node::NonTerminal(fnode) => {
-            let mut result = T::from(0);
-            let fnodeid: Vec<_> = fnode.iter().cloned().collect();
-            for x in fnodeid.into_iter() {
-                let tmp = bzmdd_count(mdd, x, ss, cache);
-                result = result + tmp;
+            mdd::Node::One => {
+                if ss.contains(&V::from(1)) {
+                    cache.insert(f, T::from(1));
+                } else {
+                    cache.insert(f, T::from(0));
+                }
+            }
+            mdd::Node::NonTerminal(fnode) if expanded => {
+                let mut result = T::from(0);
+                for x in fnode.iter() {
+                    result = result + cache.get(x).unwrap().clone();
+                }
+                cache.insert(f, result);
+            }
+            mdd::Node::NonTerminal(fnode) => {
+                next_stack.push((f, true));
+                let fnodeid: Vec<_> = fnode.iter().cloned().collect();
+                for &x in fnodeid.iter().rev() {
+                    next_stack.push((x, false));
+                }
+            }
+            mdd::Node::Undet => {
+                cache.insert(f, T::from(0));
             }
-            result
         }
-        mdd::Node::Undet => T::from(0),
-    };
-    cache.insert(key, result.clone());
-    result
+    }
+    cache.get(&node).unwrap().clone()
 }
 
 #[cfg(test)]
@@ -0,0 +1,87 @@
+use crate::bdd_diag::{best_first, Step};
+use crate::prelude::*;
+
+// the n most probable minimal vectors in the minimal solution diagram `node`
+// together with the probabilities P(x >= v); a variable missing in a vector stays at 0
+pub fn top_n<V>(
+    mdd: &mtmdd2::MtMdd2Manager<V>,
+    node: &mtmdd2::Node,
+    pv: &HashMap<String, Vec<f64>>,
+    ss: &HashSet<V>,
+    n: usize,
+) -> Vec<(HashMap<String, usize>, f64)>
+where
+    V: MDDValue,
+{
+    // tail sums; weights[label][i] = P(x >= i)
+    let weights: HashMap<String, Vec<f64>> = pv
+        .iter()
+        .map(|(k, fp)| {
+            let mut w = vec![0.0; fp.len()];
+            let mut total = 0.0;
+            for i in (0..fp.len()).rev() {
+                total += fp[i];
+                w[i] = total;
+            }
+            (k.clone(), w)
+        })
+        .collect();
+    let result = match node {
+        mtmdd2::Node::Value(fnode) => {
+            let dd = mdd.mtmdd();
+            let step = |x: NodeId| match dd.get_node(x).unwrap() {
+                mtmdd::Node::NonTerminal(fnode) => {
+                    Step::Inner(dd.label(x).unwrap().to_string(), fnode.iter().cloned().collect())
+                }
+                mtmdd::Node::Terminal(fnode) => Step::Leaf(ss.contains(&fnode.value())),
+                mtmdd::Node::Undet => Step::Leaf(false),
+            };
+            best_first(&step, *fnode, &weights, n)
+        }
+        mtmdd2::Node::Bool(fnode) => {
+            let dd = mdd.mdd();
+            let step = |x: NodeId| match dd.get_node(x).unwrap() {
+                mdd::Node::NonTerminal(fnode) => {
+                    Step::Inner(dd.label(x).unwrap().to_string(), fnode.iter().cloned().collect())
+                }
+                mdd::Node::Zero => Step::Leaf(ss.contains(&V::from(0))),
+                mdd::Node::One => Step::Leaf(ss.contains(&V::from(1))),
+                mdd::Node::Undet => Step::Leaf(false),
+            };
+            best_first(&step, *fnode, &weights, n)
+        }
+    };
+    result
+        .into_iter()
+        .map(|(path, p)| (path.into_iter().collect(), p))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mss::MddMgr;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_mdd_top_explanations() {
+        let mut mgr: MddMgr<i32> = MddMgr::new();
+        let mut vars = HashMap::new();
+        vars.insert("x".to_string(), 3);
+        vars.insert("y".to_string(), 3);
+        let mut f = mgr.rpn("x y + 3 >=", &vars).unwrap();
+        let mut pv = HashMap::new();
+        pv.insert("x".to_string(), vec![0.2, 0.3, 0.5]);
+        pv.insert("y".to_string(), vec![0.1, 0.6, 0.3]);
+        let total = f.prob(&pv, &[1]);
+        let result = f.top_explanations(&pv, &[1], 10).unwrap();
+        for (s, p) in result.iter() {
+            println!("{:?} {}", s, p);
+        }
+        // minimal vectors: (2, 1) 0.5 * 0.9, (1, 2) 0.8 * 0.3
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].0["x"], 2);
+        assert_eq!(result[0].0["y"], 1);
+        assert!((result[0].1 - 0.45 / total).abs() < 1.0e-12);
+        assert!((result[1].1 - 0.24 / total).abs() < 1.0e-12);
+    }
+}
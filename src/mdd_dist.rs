@@ -0,0 +1,218 @@
+use crate::prelude::*;
+
+// distribution of the terminal values of MDD
+#[derive(Debug, Clone)]
+pub struct MddDist<V> {
+    pmf: Vec<(V, f64)>,
+}
+
+impl<V> MddDist<V>
+where
+    V: MDDValue + AsF64,
+{
+    pub fn new(pmf: HashMap<V, f64>) -> Self {
+        let mut pmf: Vec<_> = pmf.into_iter().collect();
+        pmf.sort_by(|a, b| a.0.as_f64().partial_cmp(&b.0.as_f64()).unwrap());
+        MddDist { pmf }
+    }
+
+    // pairs of (value, probability) sorted by value
+    pub fn pmf(&self) -> &[(V, f64)] {
+        &self.pmf
+    }
+
+    pub fn prob(&self, value: V) -> f64 {
+        self.pmf
+            .iter()
+            .filter(|(v, _)| *v == value)
+            .map(|(_, p)| *p)
+            .sum()
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.pmf.iter().map(|(v, p)| v.as_f64() * p).sum()
+    }
+
+    pub fn variance(&self) -> f64 {
+        let m = self.mean();
+        self.pmf
+            .iter()
+            .map(|(v, p)| (v.as_f64() - m) * (v.as_f64() - m) * p)
+            .sum()
+    }
+
+    // P(value <= x)
+    pub fn cdf(&self, x: V) -> f64 {
+        self.pmf
+            .iter()
+            .filter(|(v, _)| v.as_f64() <= x.as_f64())
+            .map(|(_, p)| *p)
+            .sum()
+    }
+
+    // the smallest value v such that P(value <= v) >= q; None when q is not in [0,1]
+    pub fn quantile(&self, q: f64) -> Option<V> {
+        if !(0.0..=1.0).contains(&q) {
+            return None;
+        }
+        let mut total = 0.0;
+        for (v, p) in self.pmf.iter() {
+            total += p;
+            if total >= q {
+                return Some(*v);
+            }
+        }
+        self.pmf.last().map(|(v, _)| *v)
+    }
+}
+
+pub fn pmf<V>(
+    mdd: &mtmdd2::MtMdd2Manager<V>,
+    node: &mtmdd2::Node,
+    pv: &HashMap<String, Vec<f64>>,
+) -> HashMap<V, f64>
+where
+    V: MDDValue,
+{
+    match node {
+        mtmdd2::Node::Value(fnode) => {
+            let mut cache = BddHashMap::default();
+            vpmf(mdd.mtmdd(), *fnode, pv, &mut cache)
+        }
+        mtmdd2::Node::Bool(fnode) => {
+            let mut cache = BddHashMap::default();
+            bpmf(mdd.mdd(), *fnode, pv, &mut cache)
+        }
+    }
+}
+
+// post-order traversal with an explicit stack; a node is expanded first and its distribution
+// is accumulated from those of the children in the cache when it is popped again
+fn vpmf<V>(
+    mdd: &mtmdd::MtMddManager<V>,
+    node: NodeId,
+    pv: &HashMap<String, Vec<f64>>,
+    cache: &mut BddHashMap<NodeId, HashMap<V, f64>>,
+) -> HashMap<V, f64>
+where
+    V: MDDValue,
+{
+    let mut next_stack = vec![(node, false)];
+    while let Some((f, expanded)) = next_stack.pop() {
+        if cache.contains_key(&f) {
+            continue;
+        }
+        match mdd.get_node(f).unwrap() {
+            mtmdd::Node::Terminal(fnode) => {
+                let mut result = HashMap::new();
+                result.insert(fnode.value(), 1.0);
+                cache.insert(f, result);
+            }
+            mtmdd::Node::NonTerminal(fnode) if expanded => {
+                let label = mdd.label(f).unwrap();
+                let fp = pv.get(label).unwrap();
+                let mut result = HashMap::new();
+                for (i, x) in fnode.iter().enumerate() {
+                    for (&v, &p) in cache.get(x).unwrap().iter() {
+                        *result.entry(v).or_insert(0.0) += fp[i] * p;
+                    }
+                }
+                cache.insert(f, result);
+            }
+            mtmdd::Node::NonTerminal(fnode) => {
+                next_stack.push((f, true));
+                let fnodeid: Vec<_> = fnode.iter().cloned().collect();
+                for &x in fnodeid.iter().rev() {
+                    next_stack.push((x, false));
+                }
+            }
+            mtmdd::Node::Undet => {
+                cache.insert(f, HashMap::new());
+            }
+        }
+    }
+    cache.get(&node).unwrap().clone()
+}
+
+fn bpmf<V>(
+    mdd: &mdd::MddManager,
+    node: NodeId,
+    pv: &HashMap<String, Vec<f64>>,
+    cache: &mut BddHashMap<NodeId, HashMap<V, f64>>,
+) -> HashMap<V, f64>
+where
+    V: MDDValue,
+{
+    let mut next_stack = vec![(node, false)];
+    while let Some((f, expanded)) = next_stack.pop() {
+        if cache.contains_key(&f) {
+            continue;
+        }
+        match mdd.get_node(f).unwrap() {
+            mdd::Node::Zero => {
+                let mut result = HashMap::new();
+                result.insert(V::from(0), 1.0);
+                cache.insert(f, result);
+            }
+            mdd::Node::One => {
+                let mut result = HashMap::new();
+                result.insert(V::from(1), 1.0);
+                cache.insert(f, result);
+            }
+            mdd::Node::NonTerminal(fnode) if expanded => {
+                let label = mdd.label(f).unwrap();
+                let fp = pv.get(label).unwrap();
+                let mut result = HashMap::new();
+                for (i, x) in fnode.iter().enumerate() {
+                    for (&v, &p) in cache.get(x).unwrap().iter() {
+                        *result.entry(v).or_insert(0.0) += fp[i] * p;
+                    }
+                }
+                cache.insert(f, result);
+            }
+            mdd::Node::NonTerminal(fnode) => {
+                next_stack.push((f, true));
+                let fnodeid: Vec<_> = fnode.iter().cloned().collect();
+                for &x in fnodeid.iter().rev() {
+                    next_stack.push((x, false));
+                }
+            }
+            mdd::Node::Undet => {
+                cache.insert(f, HashMap::new());
+            }
+        }
+    }
+    cache.get(&node).unwrap().clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mss::MddMgr;
+
+    #[test]
+    fn test_mdd_dist() {
+        let mut mgr: MddMgr<i32> = MddMgr::new();
+        let mut vars = HashMap::new();
+        vars.insert("x".to_string(), 3);
+        vars.insert("y".to_string(), 3);
+        let f = mgr.rpn("x y +", &vars).unwrap();
+        let mut pv = HashMap::new();
+        pv.insert("x".to_string(), vec![0.2, 0.3, 0.5]);
+        pv.insert("y".to_string(), vec![0.1, 0.6, 0.3]);
+        let dist = f.dist(&pv);
+        println!("{:?}", dist.pmf());
+        let total: f64 = dist.pmf().iter().map(|(_, p)| p).sum();
+        assert!((total - 1.0).abs() < 1.0e-10);
+        assert!((dist.prob(0) - 0.02).abs() < 1.0e-10);
+        assert!((dist.mean() - (1.3 + 1.2)).abs() < 1.0e-10);
+        assert!((dist.variance() - (0.61 + 0.36)).abs() < 1.0e-10);
+        assert!((dist.cdf(1) - (0.02 + 0.2 * 0.6 + 0.3 * 0.1)).abs() < 1.0e-10);
+        assert_eq!(dist.quantile(0.5), Some(3));
+        assert_eq!(dist.quantile(0.0), Some(0));
+        assert_eq!(dist.quantile(1.0), Some(4));
+        assert_eq!(dist.quantile(1.5), None);
+        assert_eq!(dist.quantile(-0.1), None);
+        assert_eq!(dist.quantile(f64::NAN), None);
+    }
+}
@@ -0,0 +1,274 @@
+use crate::bdd_equiv::Witness;
+use crate::bdd_monotone::{find_pair, variables, Step};
+use crate::prelude::*;
+
+// one step over the value MDD for find_pair and variables
+fn vstep<V>(dd: &mtmdd::MtMddManager<V>, x: NodeId) -> Step<V>
+where
+    V: MDDValue,
+{
+    match dd.get_node(x).unwrap() {
+        mtmdd::Node::NonTerminal(fnode) => Step::Inner(
+            dd.label(x).unwrap().to_string(),
+            dd.level(x).unwrap(),
+            fnode.iter().cloned().collect(),
+        ),
+        mtmdd::Node::Terminal(fnode) => Step::Leaf(Some(fnode.value())),
+        mtmdd::Node::Undet => Step::Leaf(None),
+    }
+}
+
+// one step over the boolean MDD, where False and True take the values 0 and 1
+fn bstep<V>(dd: &mdd::MddManager, x: NodeId) -> Step<V>
+where
+    V: MDDValue,
+{
+    match dd.get_node(x).unwrap() {
+        mdd::Node::NonTerminal(fnode) => Step::Inner(
+            dd.label(x).unwrap().to_string(),
+            dd.level(x).unwrap(),
+            fnode.iter().cloned().collect(),
+        ),
+        mdd::Node::Zero => Step::Leaf(Some(V::from(0))),
+        mdd::Node::One => Step::Leaf(Some(V::from(1))),
+        mdd::Node::Undet => Step::Leaf(None),
+    }
+}
+
+// the variables in the MDD as (label, the number of states) in ascending order of levels
+fn labels<V>(mdd: &mtmdd2::MtMdd2Manager<V>, node: &mtmdd2::Node) -> Vec<(String, usize)>
+where
+    V: MDDValue,
+{
+    let states = |s: Step<V>| match s {
+        Step::Inner(_, _, children) => children.len(),
+        Step::Leaf(_) => 0,
+    };
+    match node {
+        mtmdd2::Node::Value(fnode) => {
+            let dd = mdd.mtmdd();
+            variables(&|x| vstep(dd, x), *fnode)
+                .into_iter()
+                .map(|(_, x, f)| (x, states(vstep(dd, f))))
+                .collect()
+        }
+        mtmdd2::Node::Bool(fnode) => {
+            let dd = mdd.mdd();
+            variables(&|x| bstep::<V>(dd, x), *fnode)
+                .into_iter()
+                .map(|(_, x, f)| (x, states(bstep(dd, f))))
+                .collect()
+        }
+    }
+}
+
+// copy the MDD into another manager; sels[label][i] is the boolean MDD of label == i in mdd2
+fn copy<V>(
+    mdd: &mtmdd2::MtMdd2Manager<V>,
+    node: &mtmdd2::Node,
+    mdd2: &mut mtmdd2::MtMdd2Manager<V>,
+    sels: &HashMap<String, Vec<mtmdd2::Node>>,
+) -> mtmdd2::Node
+where
+    V: MDDValue,
+{
+    match node {
+        mtmdd2::Node::Value(fnode) => vcopy(mdd.mtmdd(), *fnode, mdd2, sels, &mut BddHashMap::default()),
+        mtmdd2::Node::Bool(fnode) => bcopy(mdd.mdd(), *fnode, mdd2, sels, &mut BddHashMap::default()),
+    }
+}
+
+// the node selecting children[i] when the variable with sel is i
+fn select<V>(mdd2: &mut mtmdd2::MtMdd2Manager<V>, sel: &[mtmdd2::Node], children: &[mtmdd2::Node]) -> mtmdd2::Node
+where
+    V: MDDValue,
+{
+    let mut result = children[children.len() - 1];
+    for i in (0..children.len() - 1).rev() {
+        result = mdd2.ite(sel[i], children[i], result);
+    }
+    result
+}
+
+fn vcopy<V>(
+    dd: &mtmdd::MtMddManager<V>,
+    node: NodeId,
+    mdd2: &mut mtmdd2::MtMdd2Manager<V>,
+    sels: &HashMap<String, Vec<mtmdd2::Node>>,
+    cache: &mut BddHashMap<NodeId, mtmdd2::Node>,
+) -> mtmdd2::Node
+where
+    V: MDDValue,
+{
+    let mut next_stack = vec![(node, false)];
+    while let Some((f, expanded)) = next_stack.pop() {
+        if cache.contains_key(&f) {
+            continue;
+        }
+        match dd.get_node(f).unwrap() {
+            mtmdd::Node::Terminal(fnode) => {
+                cache.insert(f, mdd2.value(fnode.value()));
+            }
+            mtmdd::Node::NonTerminal(fnode) if expanded => {
+                let children: Vec<_> = fnode.iter().map(|x| cache[x]).collect();
+                let result = select(mdd2, &sels[dd.label(f).unwrap()], &children);
+                cache.insert(f, result);
+            }
+            mtmdd::Node::NonTerminal(fnode) => {
+                let children: Vec<NodeId> = fnode.iter().cloned().collect();
+                next_stack.push((f, true));
+                for &x in children.iter().rev() {
+                    next_stack.push((x, false));
+                }
+            }
+            mtmdd::Node::Undet => panic!("Undetermined node"),
+        }
+    }
+    cache[&node]
+}
+
+fn bcopy<V>(
+    dd: &mdd::MddManager,
+    node: NodeId,
+    mdd2: &mut mtmdd2::MtMdd2Manager<V>,
+    sels: &HashMap<String, Vec<mtmdd2::Node>>,
+    cache: &mut BddHashMap<NodeId, mtmdd2::Node>,
+) -> mtmdd2::Node
+where
+    V: MDDValue,
+{
+    let mut next_stack = vec![(node, false)];
+    while let Some((f, expanded)) = next_stack.pop() {
+        if cache.contains_key(&f) {
+            continue;
+        }
+        match dd.get_node(f).unwrap() {
+            mdd::Node::Zero => {
+                cache.insert(f, mdd2.zero());
+            }
+            mdd::Node::One => {
+                cache.insert(f, mdd2.one());
+            }
+            mdd::Node::NonTerminal(fnode) if expanded => {
+                let children: Vec<_> = fnode.iter().map(|x| cache[x]).collect();
+                let result = select(mdd2, &sels[dd.label(f).unwrap()], &children);
+                cache.insert(f, result);
+            }
+            mdd::Node::NonTerminal(fnode) => {
+                let children: Vec<NodeId> = fnode.iter().cloned().collect();
+                next_stack.push((f, true));
+                for &x in children.iter().rev() {
+                    next_stack.push((x, false));
+                }
+            }
+            mdd::Node::Undet => panic!("Undetermined node"),
+        }
+    }
+    cache[&node]
+}
+
+// an assignment on which rel(f, g) fails; both are copied into a temporary manager
+// where the variables are matched by label, and a boolean MDD takes the values 0 and 1.
+// An error when a variable has different numbers of states in f and g
+pub fn compare<V, R>(
+    mdd1: &mtmdd2::MtMdd2Manager<V>,
+    f: &mtmdd2::Node,
+    mdd2: &mtmdd2::MtMdd2Manager<V>,
+    g: &mtmdd2::Node,
+    rel: &R,
+) -> Result<Option<Witness<V>>, String>
+where
+    V: MDDValue,
+    R: Fn(&V, &V) -> bool,
+{
+    let mut order = labels(mdd1, f);
+    for (x, n) in labels(mdd2, g) {
+        match order.iter().find(|(y, _)| *y == x) {
+            Some(&(_, m)) if m != n => {
+                return Err(format!("The numbers of states of {} are different: {} and {}", x, m, n));
+            }
+            Some(_) => (),
+            None => order.push((x, n)),
+        }
+    }
+    let mut mdd = mtmdd2::MtMdd2Manager::new();
+    let mut sels = HashMap::new();
+    for (level, (x, n)) in order.iter().enumerate() {
+        let range: Vec<V> = (0..*n).map(|i| V::from(i as i32)).collect();
+        let var = mtmdd2::gen_var(&mut mdd, x, level, &range);
+        let sel: Vec<_> = range
+            .iter()
+            .map(|v| {
+                let tmp = mdd.value(v.clone());
+                mdd.eq(var, tmp)
+            })
+            .collect();
+        sels.insert(x.clone(), sel);
+    }
+    let f = copy(mdd1, f, &mut mdd, &sels);
+    let g = copy(mdd2, g, &mut mdd, &sels);
+    let (f, g) = match (f, g) {
+        (mtmdd2::Node::Value(_), mtmdd2::Node::Bool(_)) => {
+            let (x0, x1) = (mdd.value(V::from(0)), mdd.value(V::from(1)));
+            (f, mdd.ite(g, x1, x0))
+        }
+        (mtmdd2::Node::Bool(_), mtmdd2::Node::Value(_)) => {
+            let (x0, x1) = (mdd.value(V::from(0)), mdd.value(V::from(1)));
+            (mdd.ite(f, x1, x0), g)
+        }
+        _ => (f, g),
+    };
+    let mut path = Vec::new();
+    let mut checked = HashSet::new();
+    let values = match (f, g) {
+        (mtmdd2::Node::Value(f), mtmdd2::Node::Value(g)) => {
+            let dd = mdd.mtmdd();
+            find_pair(&|x| vstep(dd, x), f, g, rel, &mut path, &mut checked)
+        }
+        (mtmdd2::Node::Bool(f), mtmdd2::Node::Bool(g)) => {
+            let dd = mdd.mdd();
+            find_pair(&|x| bstep(dd, x), f, g, rel, &mut path, &mut checked)
+        }
+        _ => unreachable!(),
+    };
+    Ok(values.map(|values| Witness {
+        assignment: path.into_iter().collect(),
+        values,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mss::MddMgr;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_mdd_equivalent() {
+        let mut vars = HashMap::new();
+        vars.insert("x".to_string(), 3);
+        vars.insert("y".to_string(), 3);
+        let mut mgr1: MddMgr<i32> = MddMgr::new();
+        let f = mgr1.rpn("x y max", &vars).unwrap();
+        let mut mgr2: MddMgr<i32> = MddMgr::new();
+        let g = mgr2.rpn("y x max", &vars).unwrap();
+        assert!(f.equivalent(&g).unwrap().is_none());
+        let h = mgr2.rpn("y x +", &vars).unwrap();
+        assert!(f.implies(&h).unwrap().is_none());
+        let w = h.implies(&f).unwrap().unwrap();
+        println!("{:?}", w);
+        assert!(w.assignment["x"] > 0 && w.assignment["y"] > 0);
+        assert!(w.values.0 > w.values.1);
+        // a boolean MDD is compared as 0 and 1
+        let b = mgr2.rpn("x 1 >=", &vars).unwrap();
+        let k = mgr1.rpn("x 1 min", &vars).unwrap();
+        assert!(b.equivalent(&k).unwrap().is_none());
+        // x has three states in f and four states in m
+        let mut vars4 = HashMap::new();
+        vars4.insert("x".to_string(), 4);
+        vars4.insert("y".to_string(), 3);
+        let mut mgr3: MddMgr<i32> = MddMgr::new();
+        let m = mgr3.rpn("x y max", &vars4).unwrap();
+        assert!(f.equivalent(&m).is_err());
+        assert!(m.implies(&f).is_err());
+    }
+}
@@ -0,0 +1,183 @@
+use crate::prelude::*;
+
+// minimal s-t cuts given by the indices of edges; the edges from the side of s to the other side
+// are counted for directed networks. A cut is given by the vertex set S on the side of s such that
+// every vertex in S is reached from s inside S and every vertex next to S reaches t outside S,
+// and the sets are enumerated by backtracking from S = {s}
+pub fn min_cuts(edges: &[(usize, usize)], s: usize, t: usize, directed: bool) -> Result<Vec<Vec<usize>>, String> {
+    if s == t {
+        return Err("The source and the terminal should be different".to_string());
+    }
+    let n = edges.iter().flat_map(|&(u, v)| [u, v]).chain([s, t]).max().unwrap() + 1;
+    let mut succ = vec![Vec::new(); n];
+    let mut pred = vec![Vec::new(); n];
+    for &(u, v) in edges.iter() {
+        succ[u].push(v);
+        pred[v].push(u);
+        if !directed {
+            succ[v].push(u);
+            pred[u].push(v);
+        }
+    }
+    let cuts = CutSearch {
+        edges,
+        succ,
+        pred,
+        t,
+        directed,
+    };
+    let mut side = vec![false; n];
+    side[s] = true;
+    let mut excluded = vec![false; n];
+    let mut result = Vec::new();
+    cuts.search(&mut side, &mut excluded, &mut result);
+    result.sort();
+    result.sort_by_key(|x| x.len());
+    Ok(result)
+}
+
+struct CutSearch<'a> {
+    edges: &'a [(usize, usize)],
+    succ: Vec<Vec<usize>>,
+    pred: Vec<Vec<usize>>,
+    t: usize,
+    directed: bool,
+}
+
+impl<'a> CutSearch<'a> {
+    // the vertices reaching t without passing through the side of s
+    fn reach(&self, side: &[bool]) -> Vec<bool> {
+        let mut result = vec![false; side.len()];
+        result[self.t] = true;
+        let mut next_stack = vec![self.t];
+        while let Some(w) = next_stack.pop() {
+            for &u in self.pred[w].iter() {
+                if !side[u] && !result[u] {
+                    result[u] = true;
+                    next_stack.push(u);
+                }
+            }
+        }
+        result
+    }
+
+    // the vertices next to the side of s
+    fn boundary(&self, side: &[bool]) -> Vec<usize> {
+        let mut result: Vec<usize> = (0..side.len())
+            .filter(|&u| side[u])
+            .flat_map(|u| self.succ[u].iter().cloned())
+            .filter(|&w| !side[w])
+            .collect();
+        result.sort();
+        result.dedup();
+        result
+    }
+
+    // side grows from s and the excluded vertices stay on the side of t,
+    // so that every set is visited once
+    fn search(&self, side: &mut [bool], excluded: &mut [bool], result: &mut Vec<Vec<usize>>) {
+        let reach = self.reach(side);
+        let boundary = self.boundary(side);
+        if boundary.iter().any(|&w| excluded[w] && !reach[w]) {
+            return;
+        }
+        // a vertex that cannot reach t has to join the side of s
+        if let Some(&w) = boundary.iter().find(|&&w| !reach[w]) {
+            side[w] = true;
+            self.search(side, excluded, result);
+            side[w] = false;
+            return;
+        }
+        let cut: Vec<usize> = self
+            .edges
+            .iter()
+            .enumerate()
+            .filter(|&(_, &(u, v))| {
+                if self.directed {
+                    side[u] && !side[v]
+                } else {
+                    side[u] != side[v]
+                }
+            })
+            .map(|(i, _)| i)
+            .collect();
+        result.push(cut);
+        let candidates: Vec<usize> = boundary
+            .into_iter()
+            .filter(|&w| w != self.t && !excluded[w])
+            .collect();
+        for &w in candidates.iter() {
+            side[w] = true;
+            self.search(side, excluded, result);
+            side[w] = false;
+            excluded[w] = true;
+        }
+        for &w in candidates.iter() {
+            excluded[w] = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mss::MddMgr;
+
+    #[test]
+    fn test_min_cuts() {
+        let edges = vec![(0, 1), (1, 2), (0, 2)];
+        let cuts = min_cuts(&edges, 0, 2, false).unwrap();
+        assert_eq!(cuts, vec![vec![0, 2], vec![1, 2]]);
+    }
+
+    #[test]
+    fn test_min_cuts_bridge() {
+        let edges = vec![(0, 1), (0, 2), (1, 2), (1, 3), (2, 3)];
+        let cuts = min_cuts(&edges, 0, 3, false).unwrap();
+        assert_eq!(cuts, vec![vec![0, 1], vec![3, 4], vec![0, 2, 4], vec![1, 2, 3]]);
+        // a dead end does not change the cuts
+        let mut edges = edges;
+        edges.push((1, 4));
+        let cuts = min_cuts(&edges, 0, 3, true).unwrap();
+        assert_eq!(cuts, vec![vec![0, 1], vec![0, 4], vec![3, 4], vec![1, 2, 3]]);
+        assert!(min_cuts(&edges, 0, 0, true).is_err());
+    }
+
+    #[test]
+    fn test_min_cuts_chain() {
+        // 40 stages of two parallel pipes have the cuts of the two pipes in each stage
+        let edges: Vec<_> = (0..40).flat_map(|i| [(i, i + 1), (i, i + 1)]).collect();
+        let cuts = min_cuts(&edges, 0, 40, false).unwrap();
+        assert_eq!(cuts.len(), 40);
+        for (i, cut) in cuts.iter().enumerate() {
+            assert_eq!(cut, &vec![2 * i, 2 * i + 1]);
+        }
+    }
+
+    #[test]
+    fn test_max_flow() {
+        let mut mgr: MddMgr<i32> = MddMgr::new();
+        mgr.defvar("a", 3);
+        mgr.defvar("b", 3);
+        mgr.defvar("c", 3);
+        let edges = vec![(0, 1, "a"), (1, 2, "b"), (0, 2, "c")];
+        let f = mgr.max_flow(&edges, 0, 2, true).unwrap();
+        let mut vars = HashMap::new();
+        vars.insert("a".to_string(), 3);
+        vars.insert("b".to_string(), 3);
+        vars.insert("c".to_string(), 3);
+        let g = mgr.rpn("a b min c +", &vars).unwrap();
+        assert!(f.get_id() == g.get_id());
+        let mut h = mgr.flow_demand(&edges, 0, 2, true, 3).unwrap();
+        let mut pv = HashMap::new();
+        pv.insert("a".to_string(), vec![0.1, 0.2, 0.7]);
+        pv.insert("b".to_string(), vec![0.1, 0.2, 0.7]);
+        pv.insert("c".to_string(), vec![0.1, 0.2, 0.7]);
+        let p = h.prob(&pv, &[1]);
+        assert!((p - f.dist(&pv).pmf().iter().filter(|(v, _)| *v >= 3).map(|(_, p)| p).sum::<f64>()).abs() < 1.0e-10);
+        // d-MPs: (a, b, c) = (1, 1, 2), (2, 2, 1)
+        let dmp = h.minpath().unwrap();
+        let ss = vec![1].into_iter().collect::<HashSet<_>>();
+        assert_eq!(dmp.zmdd_count(&ss), 2);
+    }
+}
@@ -0,0 +1,167 @@
+use crate::prelude::*;
+
+// continuous-time Markov chain over the states of a multi-state component
+#[derive(Debug, Clone)]
+pub struct Ctmc {
+    q: Vec<Vec<f64>>,
+    init: Vec<f64>,
+}
+
+impl Ctmc {
+    // q is the generator matrix and init is the initial distribution
+    pub fn new(q: Vec<Vec<f64>>, init: Vec<f64>) -> Result<Self, String> {
+        let n = init.len();
+        if n == 0 {
+            return Err("The chain should have at least one state".to_string());
+        }
+        if init.iter().any(|&p| p < 0.0) {
+            return Err("The initial probabilities should be non-negative".to_string());
+        }
+        let total: f64 = init.iter().sum();
+        if (total - 1.0).abs() > 1.0e-8 {
+            return Err(format!("The initial probabilities should sum to 1: {}", total));
+        }
+        if q.len() != n || q.iter().any(|row| row.len() != n) {
+            return Err(format!("The generator matrix should be {} x {}", n, n));
+        }
+        for (i, row) in q.iter().enumerate() {
+            if let Some(j) = (0..n).find(|&j| j != i && (row[j].is_nan() || row[j] < 0.0)) {
+                return Err(format!("The transition rate from {} to {} should be non-negative", i, j));
+            }
+            let s: f64 = row.iter().sum();
+            if s.abs() > 1.0e-8 * (1.0 + row[i].abs()) {
+                return Err(format!("The row sum of the generator matrix is not zero: {}", i));
+            }
+        }
+        Ok(Ctmc { q, init })
+    }
+
+    pub fn size(&self) -> usize {
+        self.init.len()
+    }
+
+    // transient state probabilities at time t by uniformization
+    pub fn transient(&self, t: f64) -> Vec<f64> {
+        let n = self.size();
+        let qmax = (0..n).map(|i| -self.q[i][i]).fold(0.0, f64::max) * 1.01;
+        if qmax == 0.0 || t <= 0.0 {
+            return self.init.clone();
+        }
+        let lambda = qmax * t;
+        let eps = 1.0e-12;
+        let kmax = (lambda + 10.0 * lambda.sqrt() + 20.0) as usize;
+        let mut x = self.init.clone();
+        let mut result = vec![0.0; n];
+        let mut logfact = 0.0;
+        let mut total = 0.0;
+        for k in 0..=kmax {
+            if k > 0 {
+                // x = x (I + Q/qmax)
+                let mut y = x.clone();
+                for i in 0..n {
+                    for j in 0..n {
+                        y[j] += x[i] * self.q[i][j] / qmax;
+                    }
+                }
+                x = y;
+                logfact += (k as f64).ln();
+            }
+            let w = (-lambda + k as f64 * lambda.ln() - logfact).exp();
+            for j in 0..n {
+                result[j] += w * x[j];
+            }
+            total += w;
+            if k as f64 > lambda && total >= 1.0 - eps {
+                break;
+            }
+        }
+        result
+    }
+
+    // stationary distribution solving pi Q = 0 and sum(pi) = 1
+    pub fn steady(&self) -> Result<Vec<f64>, String> {
+        let n = self.size();
+        // A = Q^T with the last equation replaced by the normalization
+        let mut a: Vec<Vec<f64>> = (0..n).map(|i| (0..n).map(|j| self.q[j][i]).collect()).collect();
+        let mut b = vec![0.0; n];
+        a[n - 1] = vec![1.0; n];
+        b[n - 1] = 1.0;
+        for k in 0..n {
+            let p = (k..n)
+                .max_by(|&i, &j| a[i][k].abs().partial_cmp(&a[j][k].abs()).unwrap())
+                .unwrap();
+            if a[p][k].abs() < 1.0e-14 {
+                return Err("The generator matrix is singular".to_string());
+            }
+            a.swap(k, p);
+            b.swap(k, p);
+            for i in (k + 1)..n {
+                let r = a[i][k] / a[k][k];
+                for j in k..n {
+                    a[i][j] -= r * a[k][j];
+                }
+                b[i] -= r * b[k];
+            }
+        }
+        let mut x = vec![0.0; n];
+        for k in (0..n).rev() {
+            let s: f64 = ((k + 1)..n).map(|j| a[k][j] * x[j]).sum();
+            x[k] = (b[k] - s) / a[k][k];
+        }
+        Ok(x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mss::MddMgr;
+
+    // state 0: failed, state 1: working
+    fn repairable(lambda: f64, mu: f64) -> Ctmc {
+        Ctmc::new(vec![vec![-mu, mu], vec![lambda, -lambda]], vec![0.0, 1.0]).unwrap()
+    }
+
+    #[test]
+    fn test_ctmc() {
+        let (lambda, mu) = (0.1, 2.0);
+        let m = repairable(lambda, mu);
+        let t = 0.7;
+        let a = mu / (lambda + mu) + lambda / (lambda + mu) * (-(lambda + mu) * t).exp();
+        let p = m.transient(t);
+        assert!((p[1] - a).abs() < 1.0e-10);
+        let s = m.steady().unwrap();
+        assert!((s[1] - mu / (lambda + mu)).abs() < 1.0e-12);
+    }
+
+    #[test]
+    fn test_ctmc_invalid() {
+        let q = vec![vec![-1.0, 1.0], vec![2.0, -2.0]];
+        assert!(Ctmc::new(Vec::new(), Vec::new()).is_err());
+        assert!(Ctmc::new(q.clone(), vec![-0.5, 1.5]).is_err());
+        assert!(Ctmc::new(q.clone(), vec![0.5, 0.4]).is_err());
+        assert!(Ctmc::new(q.clone(), vec![0.5, 0.5]).is_ok());
+        assert!(Ctmc::new(vec![vec![-1.0, 0.5], vec![2.0, -2.0]], vec![0.5, 0.5]).is_err());
+        assert!(Ctmc::new(vec![vec![1.0, -1.0], vec![2.0, -2.0]], vec![0.5, 0.5]).is_err());
+    }
+
+    #[test]
+    fn test_mdd_markov() {
+        let mut mgr: MddMgr<i32> = MddMgr::new();
+        let mut vars = HashMap::new();
+        vars.insert("x".to_string(), 2);
+        vars.insert("y".to_string(), 2);
+        let mut f = mgr.rpn("x y max", &vars).unwrap();
+        mgr.set_model("x", repairable(0.1, 2.0)).unwrap();
+        mgr.set_model("y", repairable(0.2, 1.0)).unwrap();
+        assert!(mgr.set_model("z", repairable(0.2, 1.0)).is_err());
+        let pv = mgr.transient_pv(1.5);
+        let ax = pv["x"][1];
+        let ay = pv["y"][1];
+        assert!((f.prob(&pv, &[1]) - (1.0 - (1.0 - ax) * (1.0 - ay))).abs() < 1.0e-12);
+        let pv = mgr.steady_pv().unwrap();
+        let ax = 2.0 / 2.1;
+        let ay = 1.0 / 1.2;
+        assert!((f.prob(&pv, &[1]) - (1.0 - (1.0 - ax) * (1.0 - ay))).abs() < 1.0e-10);
+    }
+}
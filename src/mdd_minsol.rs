@@ -1,3 +1,4 @@
+use crate::bdd_minsol::{solve, SolChild, SolStep, WithoutStep};
 use crate::prelude::*;
 
 pub fn minsol<V>(mdd: &mut mtmdd2::MtMdd2Manager<V>, node: &mtmdd2::Node) -> mtmdd2::Node
@@ -8,219 +9,208 @@ where
         mtmdd2::Node::Value(fnode) => {
             let mut cache1 = BddHashMap::default();
             let mut cache2 = BddHashMap::default();
-            let result = vminsol(&mut mdd.mtmdd_mut(), *fnode, &mut cache1, &mut cache2);
+            let result = solve(
+                mdd.mtmdd_mut(),
+                *fnode,
+                &mut cache1,
+                &mut cache2,
+                |dd, x| vsol_step(dd, x, false),
+                |dd, f, g| vwithout_step(dd, f, g, false),
+                |dd, headerid, x| dd.create_node(headerid, x),
+            );
             mtmdd2::Node::Value(result)
         }
         mtmdd2::Node::Bool(fnode) => {
             let mut cache1 = BddHashMap::default();
             let mut cache2 = BddHashMap::default();
-            let result = bminsol(&mut mdd.mdd_mut(), *fnode, &mut cache1, &mut cache2);
+            let result = solve(
+                mdd.mdd_mut(),
+                *fnode,
+                &mut cache1,
+                &mut cache2,
+                |dd, x| bsol_step(dd, x, false),
+                |dd, f, g| bwithout_step(dd, f, g, false),
+                |dd, headerid, x| dd.create_node(headerid, x),
+            );
             mtmdd2::Node::Bool(result)
         }
     }
 }
 
-fn vminsol<V>(
-    dd: &mut mtmdd::MtMddManager<V>,
-    node: NodeId,
-    cache1: &mut BddHashMap<NodeId, NodeId>,
-    cache2: &mut BddHashMap<(NodeId, NodeId), NodeId>,
-) -> NodeId
+pub fn maxsol<V>(mdd: &mut mtmdd2::MtMdd2Manager<V>, node: &mtmdd2::Node) -> mtmdd2::Node
 where
     V: MDDValue,
 {
-    let key = node;
-    if let Some(x) = cache1.get(&key) {
-        return *x;
+    match node {
+        mtmdd2::Node::Value(fnode) => {
+            let mut cache1 = BddHashMap::default();
+            let mut cache2 = BddHashMap::default();
+            let result = solve(
+                mdd.mtmdd_mut(),
+                *fnode,
+                &mut cache1,
+                &mut cache2,
+                |dd, x| vsol_step(dd, x, true),
+                |dd, f, g| vwithout_step(dd, f, g, true),
+                |dd, headerid, x| dd.create_node(headerid, x),
+            );
+            mtmdd2::Node::Value(result)
+        }
+        mtmdd2::Node::Bool(fnode) => {
+            let mut cache1 = BddHashMap::default();
+            let mut cache2 = BddHashMap::default();
+            let result = solve(
+                mdd.mdd_mut(),
+                *fnode,
+                &mut cache1,
+                &mut cache2,
+                |dd, x| bsol_step(dd, x, true),
+                |dd, f, g| bwithout_step(dd, f, g, true),
+                |dd, headerid, x| dd.create_node(headerid, x),
+            );
+            mtmdd2::Node::Bool(result)
+        }
     }
-    let result = match dd.get_node(node).unwrap() {
-        mtmdd::Node::Terminal(_fnode) => node,
+}
+
+// minimal vectors: the state i is kept only when the state i-1 gives a different result.
+// maximal vectors: the state i is kept only when the state i+1 gives a different result,
+// and a variable skipped in the result should be read as its highest state.
+fn sol_children(fnodeid: &[NodeId], max: bool) -> Vec<SolChild> {
+    let n = fnodeid.len();
+    fnodeid
+        .iter()
+        .enumerate()
+        .map(|(i, &x)| {
+            if max && i < n - 1 {
+                SolChild::WithoutSol(x, fnodeid[i + 1])
+            } else if !max && i > 0 {
+                SolChild::WithoutSol(x, fnodeid[i - 1])
+            } else {
+                SolChild::Sol(x)
+            }
+        })
+        .collect()
+}
+
+fn vsol_step<V>(dd: &mut mtmdd::MtMddManager<V>, node: NodeId, max: bool) -> SolStep
+where
+    V: MDDValue,
+{
+    match dd.get_node(node).unwrap() {
+        mtmdd::Node::Terminal(_fnode) => SolStep::Done(node),
         mtmdd::Node::NonTerminal(fnode) => {
             let headerid = fnode.headerid();
             let fnodeid: Vec<_> = fnode.iter().cloned().collect();
-            let mut result = Vec::new();
-            for (i, x) in fnodeid.iter().enumerate() {
-                if i == 0 {
-                    let tmp = vminsol(dd, *x, cache1, cache2);
-                    result.push(tmp);
-                } else {
-                    let tmp = vminsol(dd, *x, cache1, cache2);
-                    let tmp2 = vwithout(dd, fnodeid[i - 1], tmp, cache2);
-                    result.push(tmp2);
-                }
-            }
-            dd.create_node(headerid, &result)
+            SolStep::Build(headerid, sol_children(&fnodeid, max))
         }
-        mtmdd::Node::Undet => dd.undet(),
-    };
-    cache1.insert(key, result);
-    result
+        mtmdd::Node::Undet => SolStep::Done(dd.undet()),
+    }
 }
 
-fn vwithout<V>(
-    mdd: &mut mtmdd::MtMddManager<V>,
-    f: NodeId,
-    g: NodeId, // minsol tree
-    cache: &mut BddHashMap<(NodeId, NodeId), NodeId>,
-) -> NodeId
+// f: the neighboring state, g: minsol (maxsol) tree
+fn vwithout_step<V>(mdd: &mut mtmdd::MtMddManager<V>, f: NodeId, g: NodeId, max: bool) -> WithoutStep
 where
     V: MDDValue,
 {
-    let key = (f, g);
-    if let Some(x) = cache.get(&key) {
-        return *x;
-    }
-    let result = match (mdd.get_node(f).unwrap(), mdd.get_node(g).unwrap()) {
-        (mtmdd::Node::Undet, _) => g,
-        (_, mtmdd::Node::Undet) => mdd.undet(),
+    match (mdd.get_node(f).unwrap(), mdd.get_node(g).unwrap()) {
+        (mtmdd::Node::Undet, _) => WithoutStep::Done(g),
+        (_, mtmdd::Node::Undet) => WithoutStep::Done(mdd.undet()),
         (mtmdd::Node::Terminal(fnode), mtmdd::Node::Terminal(gnode)) => {
             if fnode.value() == gnode.value() {
-                mdd.undet()
+                WithoutStep::Done(mdd.undet())
             } else {
-                g
+                WithoutStep::Done(g)
             }
         }
+        (mtmdd::Node::NonTerminal(fnode), mtmdd::Node::Terminal(_)) if max => {
+            // the variables skipped in g take the highest states
+            WithoutStep::Alias(fnode.iter().cloned().last().unwrap(), g)
+        }
         (mtmdd::Node::NonTerminal(fnode), mtmdd::Node::Terminal(_)) => {
             let headerid = fnode.headerid();
-            let fnodeid: Vec<_> = fnode.iter().cloned().collect();
-            let tmp: Vec<_> = fnodeid
-                .into_iter()
-                .map(|x| vwithout(mdd, x, g, cache))
-                .collect();
-            mdd.create_node(headerid, &tmp)
+            WithoutStep::Build(headerid, fnode.iter().map(|&x| (x, g)).collect())
         }
         (mtmdd::Node::Terminal(_), mtmdd::Node::NonTerminal(gnode)) => {
             let headerid = gnode.headerid();
-            let gnodeid: Vec<_> = gnode.iter().cloned().collect();
-            let tmp: Vec<_> = gnodeid
-                .into_iter()
-                .map(|x| vwithout(mdd, f, x, cache))
-                .collect();
-            mdd.create_node(headerid, &tmp)
+            WithoutStep::Build(headerid, gnode.iter().map(|&x| (f, x)).collect())
         }
         (mtmdd::Node::NonTerminal(fnode), mtmdd::Node::NonTerminal(_gnode))
             if mdd.level(f) > mdd.level(g) =>
         {
-            vwithout(mdd, fnode[0], g, cache)
+            // a variable skipped in g takes the lowest (highest) state
+            let x = if max {
+                fnode.iter().cloned().last().unwrap()
+            } else {
+                fnode[0]
+            };
+            WithoutStep::Alias(x, g)
         }
         (mtmdd::Node::NonTerminal(_fnode), mtmdd::Node::NonTerminal(gnode))
             if mdd.level(f) < mdd.level(g) =>
         {
             let headerid = gnode.headerid();
-            let gnodeid: Vec<_> = gnode.iter().cloned().collect();
-            let tmp: Vec<_> = gnodeid
-                .into_iter()
-                .map(|x| vwithout(mdd, f, x, cache))
-                .collect();
-            mdd.create_node(headerid, &tmp)
+            WithoutStep::Build(headerid, gnode.iter().map(|&x| (f, x)).collect())
         }
         (mtmdd::Node::NonTerminal(fnode), mtmdd::Node::NonTerminal(gnode)) => {
             let headerid = fnode.headerid();
-            let fnodeid: Vec<_> = fnode.iter().cloned().collect();
-            let gnodeid: Vec<_> = gnode.iter().cloned().collect();
-            let tmp: Vec<_> = fnodeid
-                .into_iter()
-                .zip(gnodeid.into_iter())
-                .map(|(f, g)| vwithout(mdd, f, g, cache))
-                .collect();
-            mdd.create_node(headerid, &tmp)
-        }
-    };
-    cache.insert(key, result);
-    result
+            WithoutStep::Build(headerid, fnode.iter().cloned().zip(gnode.iter().cloned()).collect())
+        }
+    }
 }
 
-fn bminsol(
-    dd: &mut mdd::MddManager,
-    node: NodeId,
-    cache1: &mut BddHashMap<NodeId, NodeId>,
-    cache2: &mut BddHashMap<(NodeId, NodeId), NodeId>,
-) -> NodeId {
-    let key = node;
-    if let Some(x) = cache1.get(&key) {
-        return *x;
-    }
-    let result = match dd.get_node(node).unwrap() {
-        mdd::Node::Zero => dd.undet(),
-        mdd::Node::One => node,
-        mdd::Node::NonTerminal(fnode) => {
+// maxsol exchanges the roles of Zero and One
+fn bsol_step(dd: &mut mdd::MddManager, node: NodeId, max: bool) -> SolStep {
+    match (dd.get_node(node).unwrap(), max) {
+        (mdd::Node::Zero, false) | (mdd::Node::One, true) => SolStep::Done(dd.undet()),
+        (mdd::Node::One, false) | (mdd::Node::Zero, true) => SolStep::Done(node),
+        (mdd::Node::NonTerminal(fnode), _) => {
             let headerid = fnode.headerid();
             let fnodeid: Vec<_> = fnode.iter().cloned().collect();
-            let mut result = Vec::new();
-            for (i, x) in fnodeid.iter().enumerate() {
-                if i == 0 {
-                    let tmp = bminsol(dd, *x, cache1, cache2);
-                    result.push(tmp);
-                } else {
-                    let tmp = bminsol(dd, *x, cache1, cache2);
-                    let tmp2 = bwithout(dd, fnodeid[i - 1], tmp, cache2);
-                    result.push(tmp2);
-                }
-            }
-            dd.create_node(headerid, &result)
+            SolStep::Build(headerid, sol_children(&fnodeid, max))
         }
-        mdd::Node::Undet => dd.undet(),
-    };
-    cache1.insert(key, result);
-    result
+        (mdd::Node::Undet, _) => SolStep::Done(dd.undet()),
+    }
 }
 
-fn bwithout(
-    mdd: &mut mdd::MddManager,
-    f: NodeId,
-    g: NodeId, // minsol tree
-    cache: &mut BddHashMap<(NodeId, NodeId), NodeId>,
-) -> NodeId {
-    let key = (f, g);
-    if let Some(x) = cache.get(&key) {
-        return *x;
-    }
-    let result = match (mdd.get_node(f).unwrap(), mdd.get_node(g).unwrap()) {
-        (mdd::Node::Undet, _) => g,
-        (_, mdd::Node::Undet) => mdd.undet(),
-        (mdd::Node::Zero, mdd::Node::One) => mdd.one(),
-        (mdd::Node::Zero, _) => g,
-        (_, mdd::Node::Zero) => mdd.undet(), // probably this case is inpossible
-        (mdd::Node::One, _) => mdd.undet(),
-        (mdd::Node::NonTerminal(fnode), mdd::Node::One) => {
+fn bwithout_step(mdd: &mut mdd::MddManager, f: NodeId, g: NodeId, max: bool) -> WithoutStep {
+    match (mdd.get_node(f).unwrap(), mdd.get_node(g).unwrap(), max) {
+        (mdd::Node::Undet, _, _) => WithoutStep::Done(g),
+        (_, mdd::Node::Undet, _) => WithoutStep::Done(mdd.undet()),
+        (mdd::Node::Zero, mdd::Node::One, false) => WithoutStep::Done(mdd.one()),
+        (mdd::Node::One, mdd::Node::Zero, true) => WithoutStep::Done(mdd.zero()),
+        (mdd::Node::Zero, _, false) | (mdd::Node::One, _, true) => WithoutStep::Done(g),
+        // probably these cases are impossible
+        (_, mdd::Node::Zero, false) | (_, mdd::Node::One, true) => WithoutStep::Done(mdd.undet()),
+        (mdd::Node::One, _, false) | (mdd::Node::Zero, _, true) => WithoutStep::Done(mdd.undet()),
+        (mdd::Node::NonTerminal(fnode), mdd::Node::Zero, true) => {
+            WithoutStep::Alias(fnode.iter().cloned().last().unwrap(), g)
+        }
+        (mdd::Node::NonTerminal(fnode), mdd::Node::One, false) => {
             let headerid = fnode.headerid();
-            let fnodeid: Vec<_> = fnode.iter().cloned().collect();
-            let tmp: Vec<_> = fnodeid
-                .into_iter()
-                .map(|x| bwithout(mdd, x, g, cache))
-                .collect();
-            mdd.create_node(headerid, &tmp)
+            WithoutStep::Build(headerid, fnode.iter().map(|&x| (x, g)).collect())
         }
-        (mdd::Node::NonTerminal(fnode), mdd::Node::NonTerminal(_gnode))
+        (mdd::Node::NonTerminal(fnode), mdd::Node::NonTerminal(_gnode), _)
             if mdd.level(f) > mdd.level(g) =>
         {
-            bwithout(mdd, fnode[0], g, cache)
+            let x = if max {
+                fnode.iter().cloned().last().unwrap()
+            } else {
+                fnode[0]
+            };
+            WithoutStep::Alias(x, g)
         }
-        (mdd::Node::NonTerminal(_fnode), mdd::Node::NonTerminal(gnode))
+        (mdd::Node::NonTerminal(_fnode), mdd::Node::NonTerminal(gnode), _)
             if mdd.level(f) < mdd.level(g) =>
         {
             let headerid = gnode.headerid();
-            let gnodeid: Vec<_> = gnode.iter().cloned().collect();
-            let tmp: Vec<_> = gnodeid
-                .into_iter()
-                .map(|x| bwithout(mdd, f, x, cache))
-                .collect();
-            mdd.create_node(headerid, &tmp)
-        }
-        (mdd::Node::NonTerminal(fnode), mdd::Node::NonTerminal(gnode)) => {
+            WithoutStep::Build(headerid, gnode.iter().map(|&x| (f, x)).collect())
+        }
+        (mdd::Node::NonTerminal(fnode), mdd::Node::NonTerminal(gnode), _) => {
             let headerid = fnode.headerid();
-            let fnodeid: Vec<_> = fnode.iter().cloned().collect();
-            let gnodeid: Vec<_> = gnode.iter().cloned().collect();
-            let tmp: Vec<_> = fnodeid
-                .into_iter()
-                .zip(gnodeid.into_iter())
-                .map(|(f, g)| bwithout(mdd, f, g, cache))
-                .collect();
-            mdd.create_node(headerid, &tmp)
-        }
-    };
-    cache.insert(key, result);
-    result
+            WithoutStep::Build(headerid, fnode.iter().cloned().zip(gnode.iter().cloned()).collect())
+        }
+    }
 }
 
 #[cfg(test)]
@@ -249,4 +239,40 @@ mod tests {
         let result = minsol(&mut mgr, &node);
         println!("{}", mgr.dot_string(result.clone()));
     }
+
+    #[test]
+    fn test_maxsol() {
+        let (node, mut mgr) = create_mdd();
+        println!("{}", mgr.dot_string(node.clone()));
+        let result = maxsol(&mut mgr, &node);
+        println!("{}", mgr.dot_string(result.clone()));
+        // upper boundary points of (x + y) * z for each value, e.g.,
+        // 0: (2, 2, 0), (0, 0, 2) and 4: (2, 2, 1), (2, 0, 2), (1, 1, 2), (0, 2, 2)
+        let expected = vec![(0, 2), (1, 2), (2, 5), (3, 2), (4, 4), (5, 0), (6, 2), (8, 1)];
+        for (v, n) in expected.into_iter() {
+            let ss = vec![v].into_iter().collect::<HashSet<_>>();
+            let count: u64 = crate::mdd_count::zmdd_count(&mgr, &result, &ss);
+            assert_eq!(count, n, "value {}", v);
+        }
+        let ss = (0..=8).collect::<HashSet<_>>();
+        let count: u64 = crate::mdd_count::zmdd_count(&mgr, &result, &ss);
+        assert_eq!(count, 18);
+    }
+
+    #[test]
+    fn test_maxsol_bool() {
+        let (node, mut mgr) = create_mdd();
+        let two = mgr.value(2);
+        let node = mgr.gte(node, two);
+        let result = maxsol(&mut mgr, &node);
+        println!("{}", mgr.dot_string(result.clone()));
+        // the maximal vectors with the value 0 are reached with ss = {0}:
+        // (2, 2, 0), (1, 0, 1), (0, 1, 1), (0, 0, 2)
+        let ss = vec![0].into_iter().collect::<HashSet<_>>();
+        let count: u64 = crate::mdd_count::zmdd_count(&mgr, &result, &ss);
+        assert_eq!(count, 4);
+        let ss = vec![1].into_iter().collect::<HashSet<_>>();
+        let count: u64 = crate::mdd_count::zmdd_count(&mgr, &result, &ss);
+        assert_eq!(count, 0);
+    }
 }
\ No newline at end of file
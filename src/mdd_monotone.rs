@@ -0,0 +1,62 @@
+use crate::bdd_monotone::{check, Step, Violation};
+use crate::prelude::*;
+
+// check that the MDD is non-decreasing in every variable with respect to the terminal values
+pub fn is_monotone<V>(mdd: &mtmdd2::MtMdd2Manager<V>, node: &mtmdd2::Node) -> Result<(), Violation<V>>
+where
+    V: MDDValue + PartialOrd,
+{
+    match node {
+        mtmdd2::Node::Value(fnode) => {
+            let dd = mdd.mtmdd();
+            let step = |x: NodeId| match dd.get_node(x).unwrap() {
+                mtmdd::Node::NonTerminal(fnode) => Step::Inner(
+                    dd.label(x).unwrap().to_string(),
+                    dd.level(x).unwrap(),
+                    fnode.iter().cloned().collect(),
+                ),
+                mtmdd::Node::Terminal(fnode) => Step::Leaf(Some(fnode.value())),
+                mtmdd::Node::Undet => Step::Leaf(None),
+            };
+            check(&step, *fnode)
+        }
+        mtmdd2::Node::Bool(fnode) => {
+            let dd = mdd.mdd();
+            let step = |x: NodeId| match dd.get_node(x).unwrap() {
+                mdd::Node::NonTerminal(fnode) => Step::Inner(
+                    dd.label(x).unwrap().to_string(),
+                    dd.level(x).unwrap(),
+                    fnode.iter().cloned().collect(),
+                ),
+                mdd::Node::Zero => Step::Leaf(Some(V::from(0))),
+                mdd::Node::One => Step::Leaf(Some(V::from(1))),
+                mdd::Node::Undet => Step::Leaf(None),
+            };
+            check(&step, *fnode)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mss::MddMgr;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_mdd_is_monotone() {
+        let mut mgr: MddMgr<i32> = MddMgr::new();
+        let mut vars = HashMap::new();
+        vars.insert("x".to_string(), 3);
+        vars.insert("y".to_string(), 3);
+        let mut f = mgr.rpn("x y min", &vars).unwrap();
+        assert!(f.is_monotone().is_ok());
+        assert!(f.minpath().is_ok());
+        let mut g = mgr.rpn("x y -", &vars).unwrap();
+        let v = g.is_monotone().unwrap_err();
+        println!("{:?}", v);
+        assert_eq!(v.var, "y");
+        assert_eq!(v.upper["y"], v.lower["y"] + 1);
+        assert!(v.values.0 > v.values.1);
+        assert!(g.minpath().is_err());
+    }
+}
@@ -63,6 +63,27 @@ where
     pub fn len(&self) -> u64 {
         self.node.mdd_count(&self.ss)
     }
+
+    // the paths with the names of the states (or their values for a variable without names)
+    pub fn named(self) -> impl Iterator<Item = HashMap<String, String>>
+    where
+        V: std::fmt::Display,
+    {
+        let node = self.node.clone();
+        self.map(move |path| named_path(&node, path))
+    }
+}
+
+fn named_path<V>(node: &MddNode<V>, path: HashMap<String, usize>) -> HashMap<String, String>
+where
+    V: MDDValue + std::fmt::Display,
+{
+    path.into_iter()
+        .map(|(label, i)| {
+            let name = node.state_name(&label, i).unwrap_or(i.to_string());
+            (label, name)
+        })
+        .collect()
 }
 
 impl<V> Iterator for MddPath<V>
@@ -220,6 +241,15 @@ where
     pub fn len(&self) -> u64 {
         self.node.zmdd_count(&self.ss)
     }
+
+    // the paths with the names of the states (or their values for a variable without names)
+    pub fn named(self) -> impl Iterator<Item = HashMap<String, String>>
+    where
+        V: std::fmt::Display,
+    {
+        let node = self.node.clone();
+        self.map(move |path| named_path(&node, path))
+    }
 }
 
 impl<V> Iterator for ZMddPath<V>
@@ -13,54 +13,80 @@ where
     match node {
         mtmdd2::Node::Value(fnode) => {
             let mut cache = BddHashMap::default();
-            vprob(&mut mdd.mtmdd_mut(), *fnode, &pv, ss, &mut cache)
+            vprob(&mut mdd.mtmdd_mut(), *fnode, &pv, ss, &HashMap::new(), &mut cache)
         }
         mtmdd2::Node::Bool(fnode) => {
             let mut cache = BddHashMap::default();
-            bprob(&mut mdd.mdd_mut(), *fnode, &pv, ss, &mut cache)
+            bprob(&mut mdd.mdd_mut(), *fnode, &pv, ss, &HashMap::new(), &mut cache)
         }
     }
 }
 
+// post-order traversal with an explicit stack; a node is expanded first
+// and evaluated when it is popped again after its children. A variable in `given`
+// takes the weights there instead of pv, and its children with None are not visited
 fn vprob<V, T>(
     mdd: &mut mtmdd::MtMddManager<V>,
     node: NodeId,
     pv: &HashMap<String, Vec<T>>,
     ss: &HashSet<V>,
+    given: &HashMap<String, Vec<Option<T>>>,
     cache: &mut BddHashMap<NodeId, T>,
 ) -> T
 where
     T: Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Clone + Copy + PartialEq + From<f64>,
     V: MDDValue,
 {
-    let key = node;
-    if let Some(x) = cache.get(&key) {
-        return x.clone();
-    }
-    let result = match mdd.get_node(node).unwrap() {
-        mtmdd::Node::Terminal(fnode) => {
-            let value = fnode.value();
-            if ss.contains(&value) {
-                T::from(1.0)
-            } else {
-                T::from(0.0)
-            }
-        }
-        mtmdd::Node::NonTerminal(fnode) => {
-            let label = mdd.label(node).unwrap();
-            let fp = pv.get(label).unwrap();
-            let mut result = T::from(0.0);
-            let fnodeid: Vec<_> = fnode.iter().cloned().collect();
-            for (i, x) in fnodeid.into_iter().enumerate() {
-                let tmp = vprob(mdd, x, pv, ss, cache);
-                result = result + fp[i] * tmp;
-            }
-            result
-        }
-        mtmdd::Node::Undet => T::from(0.0),
-    };
-    cache.insert(key, result.clone());
-    result
+    let mut next_stack = vec![(node, false)];
+    while let Some((f, expanded)) = next_stack.pop() {
+        if cache.contains_key(&f) {
+            continue;
+        }
+        match mdd.get_node(f).unwrap() {
+            mtmdd::Node::Terminal(fnode) => {
+                let value = fnode.value();
+                if ss.contains(&value) {
+                    cache.insert(f, T::from(1.0));
+                } else {
+                    cache.insert(f, T::from(0.0));
+                }
+            }
+            mtmdd::Node::NonTerminal(fnode) if expanded => {
+                let label = mdd.label(f).unwrap();
+                let mut result = T::from(0.0);
+                match given.get(label) {
+                    Some(w) => {
+                        for (i, x) in fnode.iter().enumerate() {
+                            if let Some(p) = w[i] {
+                                result = result + p * *cache.get(x).unwrap();
+                            }
+                        }
+                    }
+                    None => {
+                        let fp = pv.get(label).unwrap();
+                        for (i, x) in fnode.iter().enumerate() {
+                            result = result + fp[i] * *cache.get(x).unwrap();
+                        }
+                    }
+                }
+                cache.insert(f, result);
+            }
+            mtmdd::Node::NonTerminal(fnode) => {
+                next_stack.push((f, true));
+                let w = given.get(mdd.label(f).unwrap());
+                let fnodeid: Vec<_> = fnode.iter().cloned().collect();
+                for (i, &x) in fnodeid.iter().enumerate().rev() {
+                    if w.map_or(true, |w| w[i].is_some()) {
+                        next_stack.push((x, false));
+                    }
+                }
+            }
+            mtmdd::Node::Undet => {
+                cache.insert(f, T::from(0.0));
+            }
+        }
+    }
+    *cache.get(&node).unwrap()
 }
 
 fn bprob<V, T>(
@@ -68,44 +94,570 @@ fn bprob<V, T>(
     node: NodeId,
     pv: &HashMap<String, Vec<T>>,
     ss: &HashSet<V>,
+    given: &HashMap<String, Vec<Option<T>>>,
     cache: &mut BddHashMap<NodeId, T>,
 ) -> T
 where
     T: Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Clone + Copy + PartialEq + From<f64>,
     V: MDDValue,
 {
-    let key = node;
-    if let Some(x) = cache.get(&key) {
-        return x.clone();
+    let mut next_stack = vec![(node, false)];
+    while let Some((f, expanded)) = next_stack.pop() {
+        if cache.contains_key(&f) {
+            continue;
+        }
+        match mdd.get_node(f).unwrap() {
+            mdd::Node::Zero => {
+                if ss.contains(&V::from(0)) {
+                    cache.insert(f, T::from(1.0));
+                } else {
+                    cache.insert(f, T::from(0.0));
+                }
+            }
+            mdd::Node::One => {
+                if ss.contains(&V::from(1)) {
+                    cache.insert(f, T::from(1.0));
+                } else {
+                    cache.insert(f, T::from(0.0));
+                }
+            }
+            mdd::Node::NonTerminal(fnode) if expanded => {
+                let label = mdd.label(f).unwrap();
+                let mut result = T::from(0.0);
+                match given.get(label) {
+                    Some(w) => {
+                        for (i, x) in fnode.iter().enumerate() {
+                            if let Some(p) = w[i] {
+                                result = result + p * *cache.get(x).unwrap();
+                            }
+                        }
+                    }
+                    None => {
+                        let fp = pv.get(label).unwrap();
+                        for (i, x) in fnode.iter().enumerate() {
+                            result = result + fp[i] * *cache.get(x).unwrap();
+                        }
+                    }
+                }
+                cache.insert(f, result);
+            }
+            mdd::Node::NonTerminal(fnode) => {
+                next_stack.push((f, true));
+                let w = given.get(mdd.label(f).unwrap());
+                let fnodeid: Vec<_> = fnode.iter().cloned().collect();
+                for (i, &x) in fnodeid.iter().enumerate().rev() {
+                    if w.map_or(true, |w| w[i].is_some()) {
+                        next_stack.push((x, false));
+                    }
+                }
+            }
+            mdd::Node::Undet => {
+                cache.insert(f, T::from(0.0));
+            }
+        }
+    }
+    *cache.get(&node).unwrap()
+}
+
+// probability under evidence that each variable in `evidence` is in one of the given states;
+// the probabilities of such a variable are renormalized over the allowed states and
+// the other branches are not visited
+pub fn prob_given<V, T>(
+    mdd: &mut mtmdd2::MtMdd2Manager<V>,
+    node: &mtmdd2::Node,
+    pv: &HashMap<String, Vec<T>>,
+    ss: &HashSet<V>,
+    evidence: &HashMap<String, HashSet<usize>>,
+) -> Result<T, String>
+where
+    T: ProbValue + Div<Output = T>,
+    V: MDDValue,
+{
+    let mut given = HashMap::new();
+    for (label, states) in evidence.iter() {
+        let fp = match pv.get(label) {
+            Some(x) => x,
+            None => return Err(format!("Unknown variable {} in evidence", label)),
+        };
+        let mut total = T::from(0.0);
+        for &i in states.iter() {
+            if i >= fp.len() {
+                return Err(format!("State {} of {} is out of range", i, label));
+            }
+            total = total + fp[i];
+        }
+        if total == T::from(0.0) {
+            return Err(format!("The evidence on {} has probability zero", label));
+        }
+        let w: Vec<Option<T>> = (0..fp.len())
+            .map(|i| if states.contains(&i) { Some(fp[i] / total) } else { None })
+            .collect();
+        given.insert(label.clone(), w);
+    }
+    let result = match node {
+        mtmdd2::Node::Value(fnode) => {
+            let mut cache = BddHashMap::default();
+            vprob(&mut mdd.mtmdd_mut(), *fnode, pv, ss, &given, &mut cache)
+        }
+        mtmdd2::Node::Bool(fnode) => {
+            let mut cache = BddHashMap::default();
+            bprob(&mut mdd.mdd_mut(), *fnode, pv, ss, &given, &mut cache)
+        }
+    };
+    Ok(result)
+}
+
+// probabilities for many parameter sets in one pass; cols[level][i][k] is the probability of
+// state i of the variable at the level in the k-th parameter set
+pub fn prob_batch<V>(
+    mdd: &mtmdd2::MtMdd2Manager<V>,
+    node: &mtmdd2::Node,
+    cols: &[Vec<Vec<f64>>],
+    m: usize,
+    ss: &HashSet<V>,
+) -> Result<Vec<f64>, String>
+where
+    V: MDDValue,
+{
+    if m == 0 {
+        return Ok(Vec::new());
     }
-    let result = match mdd.get_node(node).unwrap() {
-        mdd::Node::Zero => {
-            if ss.contains(&V::from(0)) {
-                T::from(1.0)
-            } else {
-                T::from(0.0)
+    match node {
+        mtmdd2::Node::Value(fnode) => {
+            let dd = mdd.mtmdd();
+            let sorted_nodes = vtopological_sort(dd, *fnode);
+            batch(&sorted_nodes, cols, m, |f| match dd.get_node(f).unwrap() {
+                mtmdd::Node::Terminal(x) => {
+                    BatchNode::Leaf(if ss.contains(&x.value()) { 1.0 } else { 0.0 })
+                }
+                mtmdd::Node::NonTerminal(x) => BatchNode::Inner(dd.level(f).unwrap(), x.iter().cloned().collect()),
+                mtmdd::Node::Undet => BatchNode::Leaf(0.0),
+            })
+        }
+        mtmdd2::Node::Bool(fnode) => {
+            let dd = mdd.mdd();
+            let sorted_nodes = btopological_sort(dd, *fnode);
+            batch(&sorted_nodes, cols, m, |f| match dd.get_node(f).unwrap() {
+                mdd::Node::Zero => BatchNode::Leaf(if ss.contains(&V::from(0)) { 1.0 } else { 0.0 }),
+                mdd::Node::One => BatchNode::Leaf(if ss.contains(&V::from(1)) { 1.0 } else { 0.0 }),
+                mdd::Node::NonTerminal(x) => BatchNode::Inner(dd.level(f).unwrap(), x.iter().cloned().collect()),
+                mdd::Node::Undet => BatchNode::Leaf(0.0),
+            })
+        }
+    }
+}
+
+// the same as prob_batch for the parameter sets given as rows
+pub fn prob_batch_rows<V>(
+    mdd: &mtmdd2::MtMdd2Manager<V>,
+    node: &mtmdd2::Node,
+    pvs: &[HashMap<String, Vec<f64>>],
+    ss: &HashSet<V>,
+) -> Result<Vec<f64>, String>
+where
+    V: MDDValue,
+{
+    let mut levels = Vec::new();
+    match node {
+        mtmdd2::Node::Value(fnode) => {
+            let dd = mdd.mtmdd();
+            for f in vtopological_sort(dd, *fnode) {
+                if let (Some(level), Some(x)) = (dd.level(f), dd.label(f)) {
+                    levels.push((level, x.to_string()));
+                }
             }
         }
-        mdd::Node::One => {
-            if ss.contains(&V::from(1)) {
-                T::from(1.0)
-            } else {
-                T::from(0.0)
+        mtmdd2::Node::Bool(fnode) => {
+            let dd = mdd.mdd();
+            for f in btopological_sort(dd, *fnode) {
+                if let (Some(level), Some(x)) = (dd.level(f), dd.label(f)) {
+                    levels.push((level, x.to_string()));
+                }
             }
         }
-        mdd::Node::NonTerminal(fnode) => {
-            let label = mdd.label(node).unwrap();
-            let fp = pv.get(label).unwrap();
-            let mut result = T::from(0.0);
-            let fnodeid: Vec<_> = fnode.iter().cloned().collect();
-            for (i, x) in fnodeid.into_iter().enumerate() {
-                let tmp = bprob(mdd, x, pv, ss, cache);
-                result = result + fp[i] * tmp;
+    }
+    let mut cols: Vec<Vec<Vec<f64>>> = Vec::new();
+    for (level, x) in levels {
+        if cols.len() <= level {
+            cols.resize(level + 1, Vec::new());
+        }
+        if !cols[level].is_empty() {
+            continue;
+        }
+        let mut col: Vec<Vec<f64>> = Vec::new();
+        for pv in pvs.iter() {
+            let fp = match pv.get(&x) {
+                Some(fp) => fp,
+                None => return Err(format!("No probabilities of {}", x)),
+            };
+            if col.is_empty() {
+                col = vec![Vec::with_capacity(pvs.len()); fp.len()];
+            }
+            if fp.len() != col.len() {
+                return Err(format!("The number of states of {} is inconsistent", x));
+            }
+            for (c, &p) in col.iter_mut().zip(fp.iter()) {
+                c.push(p);
             }
-            result
         }
-        mdd::Node::Undet => T::from(0.0),
-    };
-    cache.insert(key, result.clone());
+        cols[level] = col;
+    }
+    prob_batch(mdd, node, &cols, pvs.len(), ss)
+}
+
+enum BatchNode {
+    Leaf(f64),
+    Inner(Level, Vec<NodeId>),
+}
+
+// sorted_nodes puts parents before children; the values of each node are stored
+// in a contiguous block of the buffer
+fn batch<F>(sorted_nodes: &[NodeId], cols: &[Vec<Vec<f64>>], m: usize, get: F) -> Result<Vec<f64>, String>
+where
+    F: Fn(NodeId) -> BatchNode,
+{
+    let mut index = BddHashMap::default();
+    let mut buffer = vec![0.0; sorted_nodes.len() * m];
+    for (k, &f) in sorted_nodes.iter().rev().enumerate() {
+        index.insert(f, k);
+        let (done, rest) = buffer.split_at_mut(k * m);
+        let row = &mut rest[..m];
+        match get(f) {
+            BatchNode::Leaf(x) => row.fill(x),
+            BatchNode::Inner(level, children) => {
+                let fp = match cols.get(level) {
+                    Some(x) if x.len() == children.len() => x,
+                    Some(x) if !x.is_empty() => {
+                        return Err(format!("{} states are given for level {} with {} states", x.len(), level, children.len()))
+                    }
+                    _ => return Err(format!("No parameters for level {}", level)),
+                };
+                for (x, p) in children.iter().zip(fp.iter()) {
+                    if p.len() != m {
+                        return Err(format!("No parameters of length {} for level {}", m, level));
+                    }
+                    let i = *index.get(x).unwrap();
+                    let child = &done[i * m..(i + 1) * m];
+                    for ((r, &q), &y) in row.iter_mut().zip(p.iter()).zip(child.iter()) {
+                        *r += q * y;
+                    }
+                }
+            }
+        }
+    }
+    let root = sorted_nodes.len() - 1;
+    Ok(buffer.split_off(root * m))
+}
+
+// reverse-mode derivatives of prob with respect to pv[label][state];
+// each element of pv is regarded as an independent parameter. A variable skipped on a path
+// contributes nothing there, so only the projection onto the simplex does not depend on the order
+pub fn grad<V, T>(
+    mdd: &mut mtmdd2::MtMdd2Manager<V>,
+    node: &mtmdd2::Node,
+    pv: &HashMap<String, Vec<T>>,
+    ss: &HashSet<V>,
+) -> HashMap<String, Vec<T>>
+where
+    T: ProbValue,
+    V: MDDValue,
+{
+    let mut result: HashMap<String, Vec<T>> = pv
+        .iter()
+        .map(|(k, v)| (k.clone(), vec![T::from(0.0); v.len()]))
+        .collect();
+    match node {
+        mtmdd2::Node::Value(fnode) => {
+            let dd = mdd.mtmdd_mut();
+            let mut cache = BddHashMap::default();
+            let mut gradcache = BddHashMap::default();
+            gradcache.insert(*fnode, T::from(1.0));
+            for f in vtopological_sort(dd, *fnode) {
+                let (label, fnodeid) = match dd.get_node(f).unwrap() {
+                    mtmdd::Node::NonTerminal(x) => {
+                        (dd.label(f).unwrap().to_string(), x.iter().cloned().collect::<Vec<_>>())
+                    }
+                    _ => continue,
+                };
+                let w = *gradcache.get(&f).unwrap_or(&T::from(0.0));
+                let fp = pv.get(&label).unwrap();
+                let g = result.get_mut(&label).unwrap();
+                for (i, x) in fnodeid.into_iter().enumerate() {
+                    let p = vprob(dd, x, pv, ss, &HashMap::new(), &mut cache);
+                    g[i] = g[i] + w * p;
+                    let tmp = *gradcache.get(&x).unwrap_or(&T::from(0.0));
+                    gradcache.insert(x, tmp + w * fp[i]);
+                }
+            }
+        }
+        mtmdd2::Node::Bool(fnode) => {
+            let dd = mdd.mdd_mut();
+            let mut cache = BddHashMap::default();
+            let mut gradcache = BddHashMap::default();
+            gradcache.insert(*fnode, T::from(1.0));
+            for f in btopological_sort(dd, *fnode) {
+                let (label, fnodeid) = match dd.get_node(f).unwrap() {
+                    mdd::Node::NonTerminal(x) => {
+                        (dd.label(f).unwrap().to_string(), x.iter().cloned().collect::<Vec<_>>())
+                    }
+                    _ => continue,
+                };
+                let w = *gradcache.get(&f).unwrap_or(&T::from(0.0));
+                let fp = pv.get(&label).unwrap();
+                let g = result.get_mut(&label).unwrap();
+                for (i, x) in fnodeid.into_iter().enumerate() {
+                    let p = bprob::<V, T>(dd, x, pv, ss, &mut cache);
+                    g[i] = g[i] + w * p;
+                    let tmp = *gradcache.get(&x).unwrap_or(&T::from(0.0));
+                    gradcache.insert(x, tmp + w * fp[i]);
+                }
+            }
+        }
+    }
+    result
+}
+
+// P(x = j | f in ss) for every variable and state in pv; the paths skipping x carry the mass
+// P - sum_k p_k g_k, which is shared among the states in proportion to p_j
+pub fn posterior<V, T>(
+    mdd: &mut mtmdd2::MtMdd2Manager<V>,
+    node: &mtmdd2::Node,
+    pv: &HashMap<String, Vec<T>>,
+    ss: &HashSet<V>,
+) -> Result<HashMap<String, Vec<T>>, String>
+where
+    T: ProbValue + Div<Output = T>,
+    V: MDDValue,
+{
+    let total = prob(mdd, node, pv, ss);
+    if total == T::from(0.0) {
+        return Err("The condition has probability zero".to_string());
+    }
+    let dp = grad(mdd, node, pv, ss);
+    let mut result = HashMap::new();
+    for (label, fp) in pv.iter() {
+        let g = dp.get(label).unwrap();
+        let mut skipped = total;
+        for (&p, &gi) in fp.iter().zip(g.iter()) {
+            skipped = skipped - p * gi;
+        }
+        let post = fp
+            .iter()
+            .zip(g.iter())
+            .map(|(&p, &gi)| p * (gi + skipped) / total)
+            .collect();
+        result.insert(label.clone(), post);
+    }
+    Ok(result)
+}
+
+// project the derivatives of each variable onto the tangent space of the simplex,
+// i.e., the directions keeping the sum of probabilities one
+pub fn project_simplex<T>(grad: &HashMap<String, Vec<T>>) -> HashMap<String, Vec<T>>
+where
+    T: ProbValue + Div<Output = T>,
+{
+    grad.iter()
+        .map(|(k, g)| {
+            let mut total = T::from(0.0);
+            for &x in g.iter() {
+                total = total + x;
+            }
+            let mean = total / T::from(g.len() as f64);
+            (k.clone(), g.iter().map(|&x| x - mean).collect())
+        })
+        .collect()
+}
+
+// chain rule through the parameterization pv[label][state] = p(theta);
+// jac[label][state][k] is the derivative of pv[label][state] with respect to theta[k]
+pub fn chain<T>(grad: &HashMap<String, Vec<T>>, jac: &HashMap<String, Vec<Vec<T>>>, nparams: usize) -> Vec<T>
+where
+    T: ProbValue,
+{
+    let mut result = vec![T::from(0.0); nparams];
+    for (label, g) in grad.iter() {
+        if let Some(j) = jac.get(label) {
+            for (i, &gi) in g.iter().enumerate() {
+                for (k, &d) in j[i].iter().enumerate() {
+                    result[k] = result[k] + gi * d;
+                }
+            }
+        }
+    }
+    result
+}
+
+// nodes in the order that parents come before children
+fn vtopological_sort<V>(mdd: &mtmdd::MtMddManager<V>, f: NodeId) -> Vec<NodeId>
+where
+    V: MDDValue,
+{
+    let mut result = Vec::new();
+    let mut visited = BddHashSet::default();
+    let mut stack = vec![(f, false)];
+    while let Some((x, done)) = stack.pop() {
+        if done {
+            result.push(x);
+            continue;
+        }
+        if !visited.insert(x) {
+            continue;
+        }
+        stack.push((x, true));
+        if let mtmdd::Node::NonTerminal(fnode) = mdd.get_node(x).unwrap() {
+            for &y in fnode.iter() {
+                if !visited.contains(&y) {
+                    stack.push((y, false));
+                }
+            }
+        }
+    }
+    result.reverse();
+    result
+}
+
+fn btopological_sort(mdd: &mdd::MddManager, f: NodeId) -> Vec<NodeId> {
+    let mut result = Vec::new();
+    let mut visited = BddHashSet::default();
+    let mut stack = vec![(f, false)];
+    while let Some((x, done)) = stack.pop() {
+        if done {
+            result.push(x);
+            continue;
+        }
+        if !visited.insert(x) {
+            continue;
+        }
+        stack.push((x, true));
+        if let mdd::Node::NonTerminal(fnode) = mdd.get_node(x).unwrap() {
+            for &y in fnode.iter() {
+                if !visited.contains(&y) {
+                    stack.push((y, false));
+                }
+            }
+        }
+    }
+    result.reverse();
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mss::MddMgr;
+
+    #[test]
+    fn test_mdd_grad() {
+        let mut mgr: MddMgr<i32> = MddMgr::new();
+        let mut vars = HashMap::new();
+        vars.insert("x".to_string(), 3);
+        vars.insert("y".to_string(), 3);
+        let mut f = mgr.rpn("x y max", &vars).unwrap();
+        let mut pv = HashMap::new();
+        pv.insert("x".to_string(), vec![0.2, 0.3, 0.5]);
+        pv.insert("y".to_string(), vec![0.1, 0.6, 0.3]);
+        let g = f.grad(&pv, &[2]);
+        println!("{:?}", g);
+        // P = 1 - (1 - px2)(1 - py2)
+        let gs = project_simplex(&g);
+        assert!((gs["x"][2] - gs["x"][0] - 0.7).abs() < 1.0e-12);
+        assert!((gs["y"][2] - gs["y"][1] - 0.5).abs() < 1.0e-12);
+        assert!(gs["x"].iter().sum::<f64>().abs() < 1.0e-12);
+        // finite difference along the simplex
+        let h = 1.0e-6;
+        let mut pv2 = pv.clone();
+        pv2.get_mut("x").unwrap()[2] += h;
+        pv2.get_mut("x").unwrap()[0] -= h;
+        let d = (f.prob(&pv2, &[2]) - f.prob(&pv, &[2])) / h;
+        assert!((gs["x"][2] - gs["x"][0] - d).abs() < 1.0e-6);
+        // p = (1 - theta, 0, theta) for x
+        let mut jac = HashMap::new();
+        jac.insert("x".to_string(), vec![vec![-1.0], vec![0.0], vec![1.0]]);
+        let dtheta = chain(&g, &jac, 1);
+        assert!((dtheta[0] - d).abs() < 1.0e-6);
+    }
+
+    #[test]
+    fn test_mdd_prob_given() {
+        let mut mgr: MddMgr<i32> = MddMgr::new();
+        let mut vars = HashMap::new();
+        vars.insert("x".to_string(), 3);
+        vars.insert("y".to_string(), 3);
+        let mut f = mgr.rpn("x y max", &vars).unwrap();
+        let mut pv = HashMap::new();
+        pv.insert("x".to_string(), vec![0.2, 0.3, 0.5]);
+        pv.insert("y".to_string(), vec![0.1, 0.6, 0.3]);
+        // x is known to be 0 or 1
+        let mut evidence = HashMap::new();
+        evidence.insert("x".to_string(), vec![0, 1]);
+        let result = f.prob_given(&pv, &[2], &evidence).unwrap();
+        println!("{:?}", result);
+        assert!((result - 0.3).abs() < 1.0e-12);
+        // the same as the restricted probabilities
+        let mut pv2 = pv.clone();
+        pv2.insert("x".to_string(), vec![0.4, 0.6, 0.0]);
+        let result = f.prob_given(&pv, &[1], &evidence).unwrap();
+        assert!((result - f.prob(&pv2, &[1])).abs() < 1.0e-12);
+        evidence.insert("y".to_string(), vec![3]);
+        assert!(f.prob_given(&pv, &[1], &evidence).is_err());
+    }
+
+    #[test]
+    fn test_mdd_prob_batch() {
+        let mut mgr: MddMgr<i32> = MddMgr::new();
+        let mut vars = HashMap::new();
+        vars.insert("x".to_string(), 3);
+        vars.insert("y".to_string(), 3);
+        let mut f = mgr.rpn("x y max", &vars).unwrap();
+        let mut g = mgr.rpn("x y + 2 >=", &vars).unwrap();
+        let mut pvs = Vec::new();
+        for k in 0..10 {
+            let a = 0.05 * k as f64;
+            let mut pv = HashMap::new();
+            pv.insert("x".to_string(), vec![a, 0.5 - a, 0.5]);
+            pv.insert("y".to_string(), vec![0.1, 0.9 - a, a]);
+            pvs.push(pv);
+        }
+        let result = f.prob_batch(&pvs, &[1, 2]).unwrap();
+        println!("{:?}", result);
+        for (pv, r) in pvs.iter().zip(result.iter()) {
+            assert!((f.prob(pv, &[1, 2]) - r).abs() < 1.0e-12);
+        }
+        let result = g.prob_batch(&pvs, &[1]).unwrap();
+        for (pv, r) in pvs.iter().zip(result.iter()) {
+            assert!((g.prob(pv, &[1]) - r).abs() < 1.0e-12);
+        }
+        assert_eq!(f.prob_batch(&[], &[2]).unwrap(), Vec::<f64>::new());
+        // the columns of x and y have an extra state
+        let cols: Vec<Vec<Vec<f64>>> = vec![vec![vec![0.2], vec![0.3], vec![0.5], vec![0.0]]; 2];
+        assert!(f.prob_batch_columns(&cols, &[2]).is_err());
+        assert!(f.prob_batch_columns(&cols[..0], &[2]).unwrap().is_empty());
+        pvs[3].remove("y");
+        assert!(f.prob_batch(&pvs, &[2]).is_err());
+    }
+
+    #[test]
+    fn test_mdd_posterior() {
+        let mut mgr: MddMgr<i32> = MddMgr::new();
+        let mut vars = HashMap::new();
+        vars.insert("x".to_string(), 3);
+        vars.insert("y".to_string(), 3);
+        vars.insert("z".to_string(), 2);
+        let f = mgr.rpn("x y max", &vars).unwrap();
+        let mut pv = HashMap::new();
+        pv.insert("x".to_string(), vec![0.2, 0.3, 0.5]);
+        pv.insert("y".to_string(), vec![0.1, 0.6, 0.3]);
+        pv.insert("z".to_string(), vec![0.4, 0.6]);
+        let post = f.posterior(&pv, &[2]).unwrap();
+        println!("{:?}", post);
+        // P = 0.65, P(x = 2, f = 2) = 0.5, P(x = 0, f = 2) = 0.2 * 0.3
+        assert!((post["x"][2] - 0.5 / 0.65).abs() < 1.0e-12);
+        assert!((post["x"][0] - 0.06 / 0.65).abs() < 1.0e-12);
+        assert!((post["y"].iter().sum::<f64>() - 1.0).abs() < 1.0e-12);
+        // z is not involved
+        assert!((post["z"][1] - 0.6).abs() < 1.0e-12);
+        let g = mgr.rpn("x y min", &vars).unwrap();
+        assert!(g.posterior(&pv, &[3]).is_err());
+    }
+}
@@ -0,0 +1,206 @@
+use crate::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeriesOp {
+    // flow transmission: the bottleneck
+    Min,
+    // task processing: 1/(1/g1 + 1/g2) computed as g1 g2 / (g1 + g2) in V; for an integer V
+    // the result is truncated (e.g. 1 and 1 give 0), so the performance levels should be
+    // scaled up enough
+    Harmonic,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParallelOp {
+    Sum,
+    Max,
+}
+
+// series-parallel composition of components with performance levels
+#[derive(Debug, Clone)]
+pub enum SpBlock {
+    Component(String),
+    Series(SeriesOp, Vec<SpBlock>),
+    Parallel(ParallelOp, Vec<SpBlock>),
+}
+
+impl SpBlock {
+    pub fn component(label: &str) -> Self {
+        SpBlock::Component(label.to_string())
+    }
+
+    pub fn series(op: SeriesOp, blocks: &[SpBlock]) -> Self {
+        SpBlock::Series(op, blocks.to_vec())
+    }
+
+    pub fn parallel(op: ParallelOp, blocks: &[SpBlock]) -> Self {
+        SpBlock::Parallel(op, blocks.to_vec())
+    }
+
+    // labels of the components in the order of appearance, with repetition
+    pub fn components(&self) -> Vec<String> {
+        let mut result = Vec::new();
+        let mut next_stack = vec![self];
+        while let Some(block) = next_stack.pop() {
+            match block {
+                SpBlock::Component(label) => result.push(label.clone()),
+                SpBlock::Series(_, blocks) | SpBlock::Parallel(_, blocks) => {
+                    next_stack.extend(blocks.iter().rev());
+                }
+            }
+        }
+        result
+    }
+}
+
+pub fn series_value<V>(op: SeriesOp, a: V, b: V) -> V
+where
+    V: MDDValue + AsF64,
+{
+    match op {
+        SeriesOp::Min => {
+            if a < b {
+                a
+            } else {
+                b
+            }
+        }
+        SeriesOp::Harmonic => {
+            let s = a + b;
+            if s == V::from(0) {
+                V::from(0)
+            } else {
+                (a * b) / s
+            }
+        }
+    }
+}
+
+pub fn parallel_value<V>(op: ParallelOp, a: V, b: V) -> V
+where
+    V: MDDValue + AsF64,
+{
+    match op {
+        ParallelOp::Sum => a + b,
+        ParallelOp::Max => {
+            if a > b {
+                a
+            } else {
+                b
+            }
+        }
+    }
+}
+
+// classical universal generating function; `comp` gives the pairs of (performance, probability)
+// of each component. The u-functions of the sub-blocks are treated as independent, so
+// a component appearing more than once is an error (the MDD by MddMgr::sp handles it)
+pub fn ugf<V, F>(block: &SpBlock, comp: &F) -> Result<HashMap<V, f64>, String>
+where
+    V: MDDValue + AsF64,
+    F: Fn(&str) -> Result<Vec<(V, f64)>, String>,
+{
+    let labels = block.components();
+    for (i, x) in labels.iter().enumerate() {
+        if labels[..i].contains(x) {
+            return Err(format!("The component {} appears more than once", x));
+        }
+    }
+    block_ugf(block, comp)
+}
+
+fn block_ugf<V, F>(block: &SpBlock, comp: &F) -> Result<HashMap<V, f64>, String>
+where
+    V: MDDValue + AsF64,
+    F: Fn(&str) -> Result<Vec<(V, f64)>, String>,
+{
+    match block {
+        SpBlock::Component(label) => {
+            let mut result = HashMap::new();
+            for (v, p) in comp(label)?.into_iter() {
+                *result.entry(v).or_insert(0.0) += p;
+            }
+            Ok(result)
+        }
+        SpBlock::Series(op, blocks) => compose(blocks, comp, |a, b| series_value(*op, a, b)),
+        SpBlock::Parallel(op, blocks) => compose(blocks, comp, |a, b| parallel_value(*op, a, b)),
+    }
+}
+
+fn compose<V, F, G>(blocks: &[SpBlock], comp: &F, op: G) -> Result<HashMap<V, f64>, String>
+where
+    V: MDDValue + AsF64,
+    F: Fn(&str) -> Result<Vec<(V, f64)>, String>,
+    G: Fn(V, V) -> V,
+{
+    if blocks.is_empty() {
+        return Err("Empty composition".to_string());
+    }
+    let mut result = block_ugf(&blocks[0], comp)?;
+    for b in blocks[1..].iter() {
+        let u = block_ugf(b, comp)?;
+        let mut tmp = HashMap::new();
+        for (&v1, &p1) in result.iter() {
+            for (&v2, &p2) in u.iter() {
+                *tmp.entry(op(v1, v2)).or_insert(0.0) += p1 * p2;
+            }
+        }
+        result = tmp;
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mss::MddMgr;
+
+    #[test]
+    fn test_sp_ugf() {
+        let mut mgr: MddMgr<i32> = MddMgr::new();
+        mgr.defvar_values("x", &[0, 20, 40]).unwrap();
+        mgr.defvar_values("y", &[0, 30]).unwrap();
+        mgr.defvar_values("z", &[0, 25, 50]).unwrap();
+        let mut pv = HashMap::new();
+        pv.insert("x".to_string(), vec![0.1, 0.3, 0.6]);
+        pv.insert("y".to_string(), vec![0.2, 0.8]);
+        pv.insert("z".to_string(), vec![0.05, 0.15, 0.8]);
+        for sop in [SeriesOp::Min, SeriesOp::Harmonic] {
+            for pop in [ParallelOp::Sum, ParallelOp::Max] {
+                let block = SpBlock::series(
+                    sop,
+                    &[
+                        SpBlock::parallel(pop, &[SpBlock::component("x"), SpBlock::component("y")]),
+                        SpBlock::component("z"),
+                    ],
+                );
+                let node = mgr.sp(&block).unwrap();
+                let dist1 = node.dist(&pv);
+                let dist2 = mgr.ugf(&block, &pv).unwrap();
+                assert_eq!(dist1.pmf().len(), dist2.pmf().len());
+                for ((v1, p1), (v2, p2)) in dist1.pmf().iter().zip(dist2.pmf().iter()) {
+                    assert_eq!(v1, v2);
+                    assert!((p1 - p2).abs() < 1.0e-10);
+                }
+            }
+        }
+        // the probabilities should cover all the states
+        pv.insert("y".to_string(), vec![0.2, 0.5, 0.3]);
+        let block = SpBlock::parallel(ParallelOp::Sum, &[SpBlock::component("x"), SpBlock::component("y")]);
+        assert!(mgr.ugf(&block, &pv).is_err());
+        pv.insert("y".to_string(), vec![1.0]);
+        assert!(mgr.ugf(&block, &pv).is_err());
+        // a repeated component is not independent of itself
+        pv.insert("y".to_string(), vec![0.2, 0.8]);
+        let block = SpBlock::series(
+            SeriesOp::Min,
+            &[
+                SpBlock::parallel(ParallelOp::Sum, &[SpBlock::component("x"), SpBlock::component("y")]),
+                SpBlock::component("x"),
+            ],
+        );
+        assert_eq!(block.components(), vec!["x".to_string(), "y".to_string(), "x".to_string()]);
+        assert!(mgr.ugf(&block, &pv).is_err());
+        assert!(mgr.sp(&block).is_ok());
+    }
+}
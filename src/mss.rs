@@ -18,17 +18,66 @@ use crate::prelude::*;
 use crate::mdd_prob;
 use crate::mdd_minsol;
 use crate::mdd_count;
+use crate::mdd_dist;
+use crate::mdd_dist::MddDist;
+use crate::mdd_ugf;
+use crate::mdd_flow;
+use crate::mdd_markov::Ctmc;
+use crate::mdd_diag;
+use crate::mdd_monotone;
+use crate::bdd_monotone::Violation;
+use crate::mdd_equiv;
+use crate::bdd_equiv::Witness;
+use crate::mdd_ugf::{ParallelOp, SeriesOp, SpBlock};
 use crate::mdd_path::MddPath;
 use crate::mdd_path::ZMddPath;
 
+// the states of a variable: the names (if given) and the values taken in each state
+#[derive(Clone, Debug)]
+pub struct Domain<V> {
+    pub names: Option<Vec<String>>,
+    pub values: Vec<V>,
+}
+
+impl<V> Domain<V>
+where
+    V: MDDValue,
+{
+    // the index of a state given by its name, or by its value for a variable without names
+    pub fn index(&self, state: &str) -> Option<usize> {
+        match &self.names {
+            Some(names) => names.iter().position(|x| x == state),
+            None => {
+                let value = state.parse::<V>().ok()?;
+                self.values.iter().position(|x| *x == value)
+            }
+        }
+    }
+
+    pub fn name(&self, index: usize) -> Option<String>
+    where
+        V: std::fmt::Display,
+    {
+        match &self.names {
+            Some(names) => names.get(index).cloned(),
+            None => self.values.get(index).map(|x| x.to_string()),
+        }
+    }
+}
+
+type Domains<V> = Rc<RefCell<HashMap<String, Domain<V>>>>;
+
 pub struct MddMgr<V> {
     mdd: Rc<RefCell<mtmdd2::MtMdd2Manager<V>>>,
     vars: HashMap<String, MddNode<V>>,
+    domains: Domains<V>,
+    models: HashMap<String, Ctmc>,
 }
 
 #[derive(Clone, Debug)]
 pub struct MddNode<V> {
     parent: Weak<RefCell<mtmdd2::MtMdd2Manager<V>>>,
+    domains: Domains<V>,
     node: mtmdd2::Node,
 }
 
@@ -36,9 +85,10 @@ impl<V> MddNode<V>
 where
     V: MDDValue,
 {
-    fn new(parent: &Rc<RefCell<mtmdd2::MtMdd2Manager<V>>>, node: mtmdd2::Node) -> Self {
+    fn new(parent: &Rc<RefCell<mtmdd2::MtMdd2Manager<V>>>, domains: &Domains<V>, node: mtmdd2::Node) -> Self {
         MddNode {
             parent: Rc::downgrade(&parent),
+            domains: domains.clone(),
             node,
         }
     }
@@ -52,6 +102,8 @@ where
         MddMgr {
             mdd: Rc::new(RefCell::new(mtmdd2::MtMdd2Manager::new())),
             vars: HashMap::new(),
+            domains: Rc::new(RefCell::new(HashMap::new())),
+            models: HashMap::new(),
         }
     }
 
@@ -62,39 +114,218 @@ where
     pub fn boolean(&self, other: bool) -> MddNode<V> {
         let mdd = self.mdd.borrow_mut();
         if other {
-            MddNode::new(&self.mdd, mdd.one())
+            MddNode::new(&self.mdd, &self.domains, mdd.one())
         } else {
-            MddNode::new(&self.mdd, mdd.zero())
+            MddNode::new(&self.mdd, &self.domains, mdd.zero())
         }
     }
 
     pub fn value(&self, value: V) -> MddNode<V> {
         let mut mdd = self.mdd.borrow_mut();
         let node = mdd.value(value);
-        MddNode::new(&self.mdd, node)
+        MddNode::new(&self.mdd, &self.domains, node)
     }
 
     pub fn create_node(&self, h: HeaderId, nodes: &[MddNode<V>]) -> MddNode<V> {
         let mut mdd = self.mdd.borrow_mut();
         let xs = nodes.iter().map(|x| x.node).collect::<Vec<_>>();
         let node = mdd.create_node(h, &xs);
-        MddNode::new(&self.mdd, node)
+        MddNode::new(&self.mdd, &self.domains, node)
     }
 
     pub fn defvar(&mut self, label: &str, range: usize) -> MddNode<V> {
         if let Some(node) = self.vars.get(label) {
             return node.clone();
-        } else {
-            let level = self.vars.len();
-            let result = {
-                let mut mdd = self.mdd.borrow_mut();
-                let range_: Vec<_> = (0..range).map(|x| V::from(x as i32)).collect(); // TODO: it should be changed in gen_var
-                let node = mtmdd2::gen_var(&mut mdd, label, level, &range_);
-                MddNode::new(&self.mdd, node)
+        }
+        let values: Vec<_> = (0..range).map(|x| V::from(x as i32)).collect();
+        self.defvar_values(label, &values).unwrap()
+    }
+
+    // defvar with the values taken by the variable in each state (e.g. performance levels);
+    // an error when the variable is already defined with other values
+    pub fn defvar_values(&mut self, label: &str, values: &[V]) -> Result<MddNode<V>, String> {
+        if let Some(node) = self.vars.get(label) {
+            if self.domains.borrow()[label].values != values {
+                return Err(format!("The variable {} is already defined with other values", label));
+            }
+            return Ok(node.clone());
+        }
+        let level = self.vars.len();
+        let result = {
+            let mut mdd = self.mdd.borrow_mut();
+            let range_: Vec<_> = values.to_vec();
+            let node = mtmdd2::gen_var(&mut mdd, label, level, &range_);
+            MddNode::new(&self.mdd, &self.domains, node)
+        };
+        self.vars.insert(label.to_string(), result.clone());
+        let domain = Domain {
+            names: None,
+            values: values.to_vec(),
+        };
+        self.domains.borrow_mut().insert(label.to_string(), domain);
+        Ok(result)
+    }
+
+    // defvar with named states; the i-th state takes the value i
+    pub fn defvar_states(&mut self, label: &str, states: &[&str]) -> Result<MddNode<V>, String> {
+        let values: Vec<_> = (0..states.len()).map(|x| V::from(x as i32)).collect();
+        self.defvar_domain(label, states, &values)
+    }
+
+    // defvar with named states and their values; an error when the variable is already
+    // defined with other states
+    pub fn defvar_domain(&mut self, label: &str, states: &[&str], values: &[V]) -> Result<MddNode<V>, String> {
+        if states.len() != values.len() {
+            return Err(format!("The numbers of states and values of {} are different", label));
+        }
+        let names: Vec<String> = states.iter().map(|x| x.to_string()).collect();
+        if let Some(node) = self.vars.get(label) {
+            let domains = self.domains.borrow();
+            let domain = &domains[label];
+            if domain.names.as_ref() != Some(&names) || domain.values != values {
+                return Err(format!("The variable {} is already defined with other states", label));
+            }
+            return Ok(node.clone());
+        }
+        let result = self.defvar_values(label, values)?;
+        self.domains.borrow_mut().get_mut(label).unwrap().names = Some(names);
+        Ok(result)
+    }
+
+    pub fn get_states(&self, label: &str) -> Option<Vec<String>> {
+        self.domains.borrow().get(label)?.names.clone()
+    }
+
+    pub fn state_index(&self, label: &str, state: &str) -> Option<usize> {
+        self.domains.borrow().get(label)?.index(state)
+    }
+
+    pub fn state_name(&self, label: &str, index: usize) -> Option<String>
+    where
+        V: std::fmt::Display,
+    {
+        self.domains.borrow().get(label)?.name(index)
+    }
+
+    // values taken by the variable in each state
+    pub fn var_values(&self, label: &str) -> Option<Vec<V>> {
+        Some(self.domains.borrow().get(label)?.values.clone())
+    }
+
+    // compile a series-parallel system into MTMDD
+    pub fn sp(&self, block: &SpBlock) -> Result<MddNode<V>, String> {
+        match block {
+            SpBlock::Component(label) => match self.vars.get(label) {
+                Some(node) => Ok(node.clone()),
+                None => Err(format!("Unknown variable: {}", label)),
+            },
+            SpBlock::Series(op, blocks) => {
+                let mut result = self.sp(blocks.first().ok_or("Empty composition")?)?;
+                for b in blocks[1..].iter() {
+                    let x = self.sp(b)?;
+                    result = match op {
+                        SeriesOp::Min => result.min(&x),
+                        SeriesOp::Harmonic => {
+                            let one = self.value(V::from(1));
+                            result.mul(&x).div(&result.add(&x).max(&one))
+                        }
+                    };
+                }
+                Ok(result)
+            }
+            SpBlock::Parallel(op, blocks) => {
+                let mut result = self.sp(blocks.first().ok_or("Empty composition")?)?;
+                for b in blocks[1..].iter() {
+                    let x = self.sp(b)?;
+                    result = match op {
+                        ParallelOp::Sum => result.add(&x),
+                        ParallelOp::Max => result.max(&x),
+                    };
+                }
+                Ok(result)
+            }
+        }
+    }
+
+    // distribution of the series-parallel system by the universal generating function
+    pub fn ugf(&self, block: &SpBlock, pv: &HashMap<String, Vec<f64>>) -> Result<MddDist<V>, String>
+    where
+        V: AsF64,
+    {
+        let comp = |label: &str| -> Result<Vec<(V, f64)>, String> {
+            let values = self
+                .var_values(label)
+                .ok_or(format!("Unknown variable: {}", label))?;
+            let probs = pv
+                .get(label)
+                .ok_or(format!("No probability for {}", label))?;
+            if probs.len() != values.len() {
+                return Err(format!("The probabilities of {} should have {} states", label, values.len()));
+            }
+            Ok(values.into_iter().zip(probs.iter().cloned()).collect())
+        };
+        Ok(MddDist::new(mdd_ugf::ugf(block, &comp)?))
+    }
+
+    // maximum flow from s to t where each edge is (u, v, label) and the value of the variable
+    // is the capacity; it is computed as the minimum capacity over the minimal s-t cuts
+    pub fn max_flow(&self, edges: &[(usize, usize, &str)], s: usize, t: usize, directed: bool) -> Result<MddNode<V>, String> {
+        let mut caps = Vec::new();
+        for &(_, _, x) in edges.iter() {
+            match self.vars.get(x) {
+                Some(node) => caps.push(node.clone()),
+                None => return Err(format!("Unknown variable: {}", x)),
+            }
+        }
+        let pairs: Vec<_> = edges.iter().map(|&(u, v, _)| (u, v)).collect();
+        let mut result: Option<MddNode<V>> = None;
+        for cut in mdd_flow::min_cuts(&pairs, s, t, directed)?.into_iter() {
+            let mut cap = self.value(V::from(0));
+            for e in cut.into_iter() {
+                cap = cap.add(&caps[e]);
+            }
+            result = match result {
+                Some(x) => Some(x.min(&cap)),
+                None => Some(cap),
             };
-            self.vars.insert(label.to_string(), result.clone());
-            result
         }
+        Ok(result.unwrap_or(self.value(V::from(0))))
+    }
+
+    // d-feasibility: whether the maximum flow from s to t is d or more
+    pub fn flow_demand(&self, edges: &[(usize, usize, &str)], s: usize, t: usize, directed: bool, d: V) -> Result<MddNode<V>, String> {
+        let flow = self.max_flow(edges, s, t, directed)?;
+        Ok(flow.ge(&self.value(d)))
+    }
+
+    // attach a CTMC whose states are the states of the variable
+    pub fn set_model(&mut self, label: &str, model: Ctmc) -> Result<(), String> {
+        let n = match self.vars.get(label).and_then(|x| x.get_header()) {
+            Some(hid) => self.mdd.borrow().mtmdd().get_header(hid).unwrap().edge_num(),
+            None => return Err(format!("Unknown variable: {}", label)),
+        };
+        if model.size() != n {
+            return Err(format!("The model of {} should have {} states", label, n));
+        }
+        self.models.insert(label.to_string(), model);
+        Ok(())
+    }
+
+    // state probabilities at time t of all the variables with models
+    pub fn transient_pv(&self, t: f64) -> HashMap<String, Vec<f64>> {
+        self.models
+            .iter()
+            .map(|(label, m)| (label.clone(), m.transient(t)))
+            .collect()
+    }
+
+    // stationary state probabilities of all the variables with models
+    pub fn steady_pv(&self) -> Result<HashMap<String, Vec<f64>>, String> {
+        let mut result = HashMap::new();
+        for (label, m) in self.models.iter() {
+            result.insert(label.clone(), m.steady()?);
+        }
+        Ok(result)
     }
 
     // pub fn var(&self, label: &str) -> Option<MddNode<V>> {
@@ -123,113 +354,113 @@ where
             match token {
                 "+" => {
                     let mut mdd = self.mdd.borrow_mut();
-                    let b = stack.pop().unwrap();
-                    let a = stack.pop().unwrap();
+                    let b = stack.pop().ok_or(format!("Too few operands for {}", token))?;
+                    let a = stack.pop().ok_or(format!("Too few operands for {}", token))?;
                     let tmp = mdd.add(a, b);
                     stack.push(tmp);
                 }
                 "-" => {
                     let mut mdd = self.mdd.borrow_mut();
-                    let b = stack.pop().unwrap();
-                    let a = stack.pop().unwrap();
+                    let b = stack.pop().ok_or(format!("Too few operands for {}", token))?;
+                    let a = stack.pop().ok_or(format!("Too few operands for {}", token))?;
                     let tmp = mdd.sub(a, b);
                     stack.push(tmp);
                 }
                 "*" => {
                     let mut mdd = self.mdd.borrow_mut();
-                    let b = stack.pop().unwrap();
-                    let a = stack.pop().unwrap();
+                    let b = stack.pop().ok_or(format!("Too few operands for {}", token))?;
+                    let a = stack.pop().ok_or(format!("Too few operands for {}", token))?;
                     let tmp = mdd.mul(a, b);
                     stack.push(tmp);
                 }
                 "/" => {
                     let mut mdd = self.mdd.borrow_mut();
-                    let b = stack.pop().unwrap();
-                    let a = stack.pop().unwrap();
+                    let b = stack.pop().ok_or(format!("Too few operands for {}", token))?;
+                    let a = stack.pop().ok_or(format!("Too few operands for {}", token))?;
                     let tmp = mdd.div(a, b);
                     stack.push(tmp);
                 }
                 "min" => {
                     let mut mdd = self.mdd.borrow_mut();
-                    let b = stack.pop().unwrap();
-                    let a = stack.pop().unwrap();
+                    let b = stack.pop().ok_or(format!("Too few operands for {}", token))?;
+                    let a = stack.pop().ok_or(format!("Too few operands for {}", token))?;
                     let tmp = mdd.min(a, b);
                     stack.push(tmp);
                 }
                 "max" => {
                     let mut mdd = self.mdd.borrow_mut();
-                    let b = stack.pop().unwrap();
-                    let a = stack.pop().unwrap();
+                    let b = stack.pop().ok_or(format!("Too few operands for {}", token))?;
+                    let a = stack.pop().ok_or(format!("Too few operands for {}", token))?;
                     let tmp = mdd.max(a, b);
                     stack.push(tmp);
                 }
                 "==" => {
                     let mut mdd = self.mdd.borrow_mut();
-                    let b = stack.pop().unwrap();
-                    let a = stack.pop().unwrap();
+                    let b = stack.pop().ok_or(format!("Too few operands for {}", token))?;
+                    let a = stack.pop().ok_or(format!("Too few operands for {}", token))?;
                     let tmp = mdd.eq(a, b);
                     stack.push(tmp);
                 }
                 "!=" => {
                     let mut mdd = self.mdd.borrow_mut();
-                    let b = stack.pop().unwrap();
-                    let a = stack.pop().unwrap();
+                    let b = stack.pop().ok_or(format!("Too few operands for {}", token))?;
+                    let a = stack.pop().ok_or(format!("Too few operands for {}", token))?;
                     let tmp = mdd.neq(a, b);
                     stack.push(tmp);
                 }
                 "<" => {
                     let mut mdd = self.mdd.borrow_mut();
-                    let b = stack.pop().unwrap();
-                    let a = stack.pop().unwrap();
+                    let b = stack.pop().ok_or(format!("Too few operands for {}", token))?;
+                    let a = stack.pop().ok_or(format!("Too few operands for {}", token))?;
                     let tmp = mdd.lt(a, b);
                     stack.push(tmp);
                 }
                 "<=" => {
                     let mut mdd = self.mdd.borrow_mut();
-                    let b = stack.pop().unwrap();
-                    let a = stack.pop().unwrap();
+                    let b = stack.pop().ok_or(format!("Too few operands for {}", token))?;
+                    let a = stack.pop().ok_or(format!("Too few operands for {}", token))?;
                     let tmp = mdd.lte(a, b);
                     stack.push(tmp);
                 }
                 ">" => {
                     let mut mdd = self.mdd.borrow_mut();
-                    let b = stack.pop().unwrap();
-                    let a = stack.pop().unwrap();
+                    let b = stack.pop().ok_or(format!("Too few operands for {}", token))?;
+                    let a = stack.pop().ok_or(format!("Too few operands for {}", token))?;
                     let tmp = mdd.gt(a, b);
                     stack.push(tmp);
                 }
                 ">=" => {
                     let mut mdd = self.mdd.borrow_mut();
-                    let b = stack.pop().unwrap();
-                    let a = stack.pop().unwrap();
+                    let b = stack.pop().ok_or(format!("Too few operands for {}", token))?;
+                    let a = stack.pop().ok_or(format!("Too few operands for {}", token))?;
                     let tmp = mdd.gte(a, b);
                     stack.push(tmp);
                 }
                 "&&" => {
                     let mut mdd = self.mdd.borrow_mut();
-                    let b = stack.pop().unwrap();
-                    let a = stack.pop().unwrap();
+                    let b = stack.pop().ok_or(format!("Too few operands for {}", token))?;
+                    let a = stack.pop().ok_or(format!("Too few operands for {}", token))?;
                     let tmp = mdd.and(a, b);
                     stack.push(tmp);
                 }
                 "||" => {
                     let mut mdd = self.mdd.borrow_mut();
-                    let b = stack.pop().unwrap();
-                    let a = stack.pop().unwrap();
+                    let b = stack.pop().ok_or(format!("Too few operands for {}", token))?;
+                    let a = stack.pop().ok_or(format!("Too few operands for {}", token))?;
                     let tmp = mdd.or(a, b);
                     stack.push(tmp);
                 }
                 "!" => {
                     let mut mdd = self.mdd.borrow_mut();
-                    let a = stack.pop().unwrap();
+                    let a = stack.pop().ok_or(format!("Too few operands for {}", token))?;
                     let tmp = mdd.not(a);
                     stack.push(tmp);
                 }
                 "?" => {
                     let mut mdd = self.mdd.borrow_mut();
-                    let c = stack.pop().unwrap();
-                    let b = stack.pop().unwrap();
-                    let a = stack.pop().unwrap();
+                    let c = stack.pop().ok_or(format!("Too few operands for {}", token))?;
+                    let b = stack.pop().ok_or(format!("Too few operands for {}", token))?;
+                    let a = stack.pop().ok_or(format!("Too few operands for {}", token))?;
                     let tmp = mdd.ite(a, b, c);
                     stack.push(tmp);
                 }
@@ -278,14 +509,14 @@ where
                                 let node = self.defvar(token, range.clone());
                                 stack.push(node.node.clone());
                             }
-                            None => panic!("Unknown variable: {}", token),
+                            None => return Err(format!("Unknown variable: {}", token)),
                         },
                     }
                 }
             }
         }
         if stack.len() == 1 {
-            Ok(MddNode::new(&self.mdd, stack.pop().unwrap()))
+            Ok(MddNode::new(&self.mdd, &self.domains, stack.pop().unwrap()))
         } else {
             Err("Invalid expression".to_string())
         }
@@ -298,7 +529,7 @@ where
         for node in xs {
             result = mdd.and(result, *node);
         }
-        MddNode::new(&self.mdd, result)
+        MddNode::new(&self.mdd, &self.domains, result)
     }
 
     pub fn or(&self, nodes: &[MddNode<V>]) -> MddNode<V> {
@@ -308,7 +539,7 @@ where
         for node in xs {
             result = mdd.or(result, *node);
         }
-        MddNode::new(&self.mdd, result)
+        MddNode::new(&self.mdd, &self.domains, result)
     }
 
     pub fn min(&self, nodes: &[MddNode<V>]) -> MddNode<V> {
@@ -318,7 +549,7 @@ where
         for &node in xs[1..].iter() {
             result = mdd.min(result, *node);
         }
-        MddNode::new(&self.mdd, result)
+        MddNode::new(&self.mdd, &self.domains, result)
     }
 
     pub fn max(&self, nodes: &[MddNode<V>]) -> MddNode<V> {
@@ -328,19 +559,19 @@ where
         for &node in xs[1..].iter() {
             result = mdd.max(result, *node);
         }
-        MddNode::new(&self.mdd, result)
+        MddNode::new(&self.mdd, &self.domains, result)
     }
 
     // pub fn not(&mut self, node: &MddNode<V>) -> MddNode<V> {
     //     let mut mdd = self.mdd.borrow_mut();
     //     let result = mdd.not(node.node);
-    //     MddNode::new(&self.mdd, result)
+    //     MddNode::new(&self.mdd, &self.domains, result)
     // }
 
     // pub fn ifelse(&mut self, cond: &MddNode<V>, then: &MddNode<V>, els: &MddNode<V>) -> MddNode<V> {
     //     let mut mdd = self.mdd.borrow_mut();
     //     let result = mdd.ite(cond.node, then.node, els.node);
-    //     MddNode::new(&self.mdd, result)
+    //     MddNode::new(&self.mdd, &self.domains, result)
     // }
 }
 
@@ -505,119 +736,144 @@ where
         let mddmgr = self.parent.upgrade().unwrap();
         let mut mdd = mddmgr.borrow_mut();
         let node = mdd.add(self.node, other.node);
-        MddNode::new(&mddmgr, node)
+        MddNode::new(&mddmgr, &self.domains, node)
     }
 
     pub fn sub(&self, other: &MddNode<V>) -> MddNode<V> {
         let mddmgr = self.parent.upgrade().unwrap();
         let mut mdd = mddmgr.borrow_mut();
         let node = mdd.sub(self.node, other.node);
-        MddNode::new(&mddmgr, node)
+        MddNode::new(&mddmgr, &self.domains, node)
     }
 
     pub fn mul(&self, other: &MddNode<V>) -> MddNode<V> {
         let mddmgr = self.parent.upgrade().unwrap();
         let mut mdd = mddmgr.borrow_mut();
         let node = mdd.mul(self.node, other.node);
-        MddNode::new(&mddmgr, node)
+        MddNode::new(&mddmgr, &self.domains, node)
     }
 
     pub fn div(&self, other: &MddNode<V>) -> MddNode<V> {
         let mddmgr = self.parent.upgrade().unwrap();
         let mut mdd = mddmgr.borrow_mut();
         let node = mdd.div(self.node, other.node);
-        MddNode::new(&mddmgr, node)
+        MddNode::new(&mddmgr, &self.domains, node)
     }
 
     pub fn min(&self, other: &MddNode<V>) -> MddNode<V> {
         let mddmgr = self.parent.upgrade().unwrap();
         let mut mdd = mddmgr.borrow_mut();
         let node = mdd.min(self.node, other.node);
-        MddNode::new(&mddmgr, node)
+        MddNode::new(&mddmgr, &self.domains, node)
     }
 
     pub fn max(&self, other: &MddNode<V>) -> MddNode<V> {
         let mddmgr = self.parent.upgrade().unwrap();
         let mut mdd = mddmgr.borrow_mut();
         let node = mdd.max(self.node, other.node);
-        MddNode::new(&mddmgr, node)
+        MddNode::new(&mddmgr, &self.domains, node)
     }
 
     pub fn eq(&self, other: &MddNode<V>) -> MddNode<V> {
         let mddmgr = self.parent.upgrade().unwrap();
         let mut mdd = mddmgr.borrow_mut();
         let node = mdd.eq(self.node, other.node);
-        MddNode::new(&mddmgr, node)
+        MddNode::new(&mddmgr, &self.domains, node)
     }
 
     pub fn ne(&self, other: &MddNode<V>) -> MddNode<V> {
         let mddmgr = self.parent.upgrade().unwrap();
         let mut mdd = mddmgr.borrow_mut();
         let node = mdd.neq(self.node, other.node);
-        MddNode::new(&mddmgr, node)
+        MddNode::new(&mddmgr, &self.domains, node)
     }
 
     pub fn lt(&self, other: &MddNode<V>) -> MddNode<V> {
         let mddmgr = self.parent.upgrade().unwrap();
         let mut mdd = mddmgr.borrow_mut();
         let node = mdd.lt(self.node, other.node);
-        MddNode::new(&mddmgr, node)
+        MddNode::new(&mddmgr, &self.domains, node)
     }
 
     pub fn le(&self, other: &MddNode<V>) -> MddNode<V> {
         let mddmgr = self.parent.upgrade().unwrap();
         let mut mdd = mddmgr.borrow_mut();
         let node = mdd.lte(self.node, other.node);
-        MddNode::new(&mddmgr, node)
+        MddNode::new(&mddmgr, &self.domains, node)
     }
 
     pub fn gt(&self, other: &MddNode<V>) -> MddNode<V> {
         let mddmgr = self.parent.upgrade().unwrap();
         let mut mdd = mddmgr.borrow_mut();
         let node = mdd.gt(self.node, other.node);
-        MddNode::new(&mddmgr, node)
+        MddNode::new(&mddmgr, &self.domains, node)
     }
 
     pub fn ge(&self, other: &MddNode<V>) -> MddNode<V> {
         let mddmgr = self.parent.upgrade().unwrap();
         let mut mdd = mddmgr.borrow_mut();
         let node = mdd.gte(self.node, other.node);
-        MddNode::new(&mddmgr, node)
+        MddNode::new(&mddmgr, &self.domains, node)
     }
 
     pub fn and(&self, other: &MddNode<V>) -> MddNode<V> {
         let mddmgr = self.parent.upgrade().unwrap();
         let mut mdd = mddmgr.borrow_mut();
         let node = mdd.and(self.node, other.node);
-        MddNode::new(&mddmgr, node)
+        MddNode::new(&mddmgr, &self.domains, node)
     }
 
     pub fn or(&self, other: &MddNode<V>) -> MddNode<V> {
         let mddmgr = self.parent.upgrade().unwrap();
         let mut mdd = mddmgr.borrow_mut();
         let node = mdd.or(self.node, other.node);
-        MddNode::new(&mddmgr, node)
+        MddNode::new(&mddmgr, &self.domains, node)
     }
 
     pub fn xor(&self, other: &MddNode<V>) -> MddNode<V> {
         let mddmgr = self.parent.upgrade().unwrap();
         let mut mdd = mddmgr.borrow_mut();
         let node = mdd.xor(self.node, other.node);
-        MddNode::new(&mddmgr, node)
+        MddNode::new(&mddmgr, &self.domains, node)
     }
 
     pub fn not(&self) -> MddNode<V> {
         let mddmgr = self.parent.upgrade().unwrap();
         let mut mdd = mddmgr.borrow_mut();
         let node = mdd.not(self.node);
-        MddNode::new(&mddmgr, node)
+        MddNode::new(&mddmgr, &self.domains, node)
     }
 
     pub fn ite(&self, then: &MddNode<V>, els: &MddNode<V>) -> MddNode<V> {
         let mddmgr = self.parent.upgrade().unwrap();
         let mut mdd = mddmgr.borrow_mut();
         let node = mdd.ite(self.node, then.node, els.node);
-        MddNode::new(&mddmgr, node)
+        MddNode::new(&mddmgr, &self.domains, node)
+    }
+
+    // check that the value of self never exceeds that of other; other may belong to
+    // another manager, and an assignment violating it is returned otherwise.
+    // An error when a variable has different numbers of states in self and other
+    pub fn implies(&self, other: &MddNode<V>) -> Result<Option<Witness<V>>, String>
+    where
+        V: PartialOrd,
+    {
+        self.compare(other, |a, b| a <= b)
+    }
+
+    // check that self and other take the same values, matching the variables by label
+    pub fn equivalent(&self, other: &MddNode<V>) -> Result<Option<Witness<V>>, String> {
+        self.compare(other, |a, b| a == b)
+    }
+
+    fn compare<R>(&self, other: &MddNode<V>, rel: R) -> Result<Option<Witness<V>>, String>
+    where
+        R: Fn(&V, &V) -> bool,
+    {
+        let mgr1 = self.parent.upgrade().unwrap();
+        let mgr2 = other.parent.upgrade().unwrap();
+        let result = mdd_equiv::compare(&mgr1.borrow(), &self.node, &mgr2.borrow(), &other.node, &rel);
+        result
     }
 
     pub fn prob<T>(&mut self, pv: &HashMap<String, Vec<T>>, ss: &[V]) -> T
@@ -636,11 +892,195 @@ where
         mdd_prob::prob(&mut mdd, &self.node, pv, &hashset)
     }
 
-    pub fn minpath(&mut self) -> MddNode<V> {
+    // the index of a state of the variable given by its name (or its value)
+    pub fn state_index(&self, label: &str, state: &str) -> Option<usize> {
+        self.domains.borrow().get(label)?.index(state)
+    }
+
+    // the name of a state of the variable, or its value for a variable without names
+    pub fn state_name(&self, label: &str, index: usize) -> Option<String>
+    where
+        V: std::fmt::Display,
+    {
+        self.domains.borrow().get(label)?.name(index)
+    }
+
+    // prob with the probabilities of the states given by their names (or values);
+    // a state that is not given has probability zero
+    pub fn prob_named<T>(&mut self, pv: &HashMap<String, HashMap<String, T>>, ss: &[V]) -> Result<T, String>
+    where
+        T: ProbValue,
+    {
+        let mut result = HashMap::new();
+        for (label, probs) in pv.iter() {
+            let v = {
+                let domains = self.domains.borrow();
+                let domain = domains.get(label).ok_or(format!("Unknown variable: {}", label))?;
+                let mut v = vec![T::from(0.0); domain.values.len()];
+                for (state, p) in probs.iter() {
+                    match domain.index(state) {
+                        Some(i) => v[i] = *p,
+                        None => return Err(format!("Unknown state {} of variable {}", state, label)),
+                    }
+                }
+                v
+            };
+            result.insert(label.clone(), v);
+        }
+        Ok(self.prob(&result, ss))
+    }
+
+    // distribution of the terminal value computed in a single bottom-up pass
+    pub fn dist(&self, pv: &HashMap<String, Vec<f64>>) -> MddDist<V>
+    where
+        V: AsF64,
+    {
+        let mgr = self.parent.upgrade().unwrap();
+        let mdd = mgr.borrow();
+        MddDist::new(mdd_dist::pmf(&mdd, &self.node, pv))
+    }
+
+    // probabilities for many parameter sets in one pass over the diagram
+    pub fn prob_batch(&self, pvs: &[HashMap<String, Vec<f64>>], ss: &[V]) -> Result<Vec<f64>, String> {
+        let mgr = self.parent.upgrade().unwrap();
+        let mdd = mgr.borrow();
+        let hashset: HashSet<V> = ss.iter().cloned().collect();
+        mdd_prob::prob_batch_rows(&mdd, &self.node, pvs, &hashset)
+    }
+
+    // the same as prob_batch; cols[l][i] is the column of state i of the variable at level l,
+    // i.e., the l-th defined variable
+    pub fn prob_batch_columns(&self, cols: &[Vec<Vec<f64>>], ss: &[V]) -> Result<Vec<f64>, String> {
+        let mgr = self.parent.upgrade().unwrap();
+        let mdd = mgr.borrow();
+        let hashset: HashSet<V> = ss.iter().cloned().collect();
+        let m = cols.first().and_then(|x| x.first()).map_or(0, |x| x.len());
+        mdd_prob::prob_batch(&mdd, &self.node, cols, m, &hashset)
+    }
+
+    // probability given that each variable in `evidence` is in one of the listed states
+    pub fn prob_given<T>(
+        &self,
+        pv: &HashMap<String, Vec<T>>,
+        ss: &[V],
+        evidence: &HashMap<String, Vec<usize>>,
+    ) -> Result<T, String>
+    where
+        T: ProbValue + Div<Output = T>,
+    {
+        let mgr = self.parent.upgrade().unwrap();
+        let mut mdd = mgr.borrow_mut();
+        let hashset: HashSet<V> = ss.iter().cloned().collect();
+        let evidence: HashMap<String, HashSet<usize>> = evidence
+            .iter()
+            .map(|(k, v)| (k.clone(), v.iter().cloned().collect()))
+            .collect();
+        mdd_prob::prob_given(&mut mdd, &self.node, pv, &hashset, &evidence)
+    }
+
+    // derivatives of prob with respect to pv[label][state] by a reverse-mode sweep
+    pub fn grad<T>(&self, pv: &HashMap<String, Vec<T>>, ss: &[V]) -> HashMap<String, Vec<T>>
+    where
+        T: ProbValue,
+    {
+        let mgr = self.parent.upgrade().unwrap();
+        let mut mdd = mgr.borrow_mut();
+        let hashset: HashSet<V> = ss.iter().cloned().collect();
+        mdd_prob::grad(&mut mdd, &self.node, pv, &hashset)
+    }
+
+    // P(x = j | the value is in ss) for every variable and state in pv
+    pub fn posterior<T>(&self, pv: &HashMap<String, Vec<T>>, ss: &[V]) -> Result<HashMap<String, Vec<T>>, String>
+    where
+        T: ProbValue + Div<Output = T>,
+    {
+        let mgr = self.parent.upgrade().unwrap();
+        let mut mdd = mgr.borrow_mut();
+        let hashset: HashSet<V> = ss.iter().cloned().collect();
+        mdd_prob::posterior(&mut mdd, &self.node, pv, &hashset)
+    }
+
+    // derivatives projected onto the simplex of each variable
+    pub fn grad_simplex<T>(&self, pv: &HashMap<String, Vec<T>>, ss: &[V]) -> HashMap<String, Vec<T>>
+    where
+        T: ProbValue + Div<Output = T>,
+    {
+        mdd_prob::project_simplex(&self.grad(pv, ss))
+    }
+
+    // check that the MDD is monotone in the terminal values; a counterexample is returned otherwise
+    pub fn is_monotone(&self) -> Result<(), Violation<V>>
+    where
+        V: PartialOrd,
+    {
+        let mgr = self.parent.upgrade().unwrap();
+        let mdd = mgr.borrow();
+        mdd_monotone::is_monotone(&mdd, &self.node)
+    }
+
+    // obtain minimal path vectors of monotone MDD; an error for non-monotone MDD
+    pub fn minpath(&mut self) -> Result<MddNode<V>, String>
+    where
+        V: PartialOrd,
+    {
+        if let Err(v) = self.is_monotone() {
+            return Err(format!("The MDD is not monotone in {}", v.var));
+        }
+        Ok(self.minpath_unchecked())
+    }
+
+    // minpath without the monotonicity check
+    pub fn minpath_unchecked(&mut self) -> MddNode<V> {
         let mgr = self.parent.upgrade().unwrap();
         let mut mdd = mgr.borrow_mut();
         let node = mdd_minsol::minsol(&mut mdd, &self.node);
-        MddNode::new(&mgr, node)
+        MddNode::new(&mgr, &self.domains, node)
+    }
+
+    // the n most probable minimal vectors v given that the value is in ss,
+    // with the posterior probabilities P(x >= v | the value is in ss)
+    pub fn top_explanations(
+        &mut self,
+        pv: &HashMap<String, Vec<f64>>,
+        ss: &[V],
+        n: usize,
+    ) -> Result<Vec<(HashMap<String, usize>, f64)>, String>
+    where
+        V: PartialOrd,
+    {
+        let total = self.prob(pv, ss);
+        if total == 0.0 {
+            return Err("The system state has probability zero".to_string());
+        }
+        let path = self.minpath()?;
+        let mgr = self.parent.upgrade().unwrap();
+        let mdd = mgr.borrow();
+        let hashset: HashSet<V> = ss.iter().cloned().collect();
+        let result = mdd_diag::top_n(&mdd, &path.node, pv, &hashset, n);
+        Ok(result.into_iter().map(|(s, p)| (s, p / total)).collect())
+    }
+
+    // obtain maximal cut vectors (upper boundary points) of monotone MDD;
+    // a variable that does not appear in a path takes its highest state.
+    // The vectors are enumerated by zmdd_extract with the values of interest as ss;
+    // for a boolean MDD, ss = {0} gives the maximal vectors where the value is False.
+    // An error for non-monotone MDD
+    pub fn maxcut(&mut self) -> Result<MddNode<V>, String>
+    where
+        V: PartialOrd,
+    {
+        if let Err(v) = self.is_monotone() {
+            return Err(format!("The MDD is not monotone in {}", v.var));
+        }
+        Ok(self.maxcut_unchecked())
+    }
+
+    // maxcut without the monotonicity check
+    pub fn maxcut_unchecked(&mut self) -> MddNode<V> {
+        let mgr = self.parent.upgrade().unwrap();
+        let mut mdd = mgr.borrow_mut();
+        let node = mdd_minsol::maxsol(&mut mdd, &self.node);
+        MddNode::new(&mgr, &self.domains, node)
     }
 
     pub fn mdd_count(&self, ss: &HashSet<V>) -> u64 {
@@ -692,4 +1132,99 @@ mod tests {
             println!("{}", node.dot());
         }
     }
+
+    #[test]
+    fn test_mdd_maxcut() {
+        let mut mgr: MddMgr<i32> = MddMgr::new();
+        let mut vars = HashMap::new();
+        vars.insert("x".to_string(), 3);
+        vars.insert("y".to_string(), 3);
+        vars.insert("z".to_string(), 3);
+        let mut f = mgr.rpn("x y + z *", &vars).unwrap();
+        let value = |p: [usize; 3]| ((p[0] + p[1]) * p[2]) as i32;
+        // the upper boundary points by definition: raising any state changes the value
+        let upper = |v: i32| {
+            let mut result = HashSet::new();
+            for x in 0..3 {
+                for y in 0..3 {
+                    for z in 0..3 {
+                        let p = [x, y, z];
+                        let maximal = (0..3).all(|i| {
+                            let mut q = p;
+                            q[i] += 1;
+                            q[i] > 2 || value(q) != v
+                        });
+                        if value(p) == v && maximal {
+                            result.insert(p);
+                        }
+                    }
+                }
+            }
+            result
+        };
+        let points = |node: &MddNode<i32>, v: i32| {
+            let ss = vec![v].into_iter().collect::<HashSet<_>>();
+            node.zmdd_extract(&ss)
+                .map(|p| {
+                    let state = |x: &str| p.get(x).cloned().unwrap_or(2);
+                    [state("x"), state("y"), state("z")]
+                })
+                .collect::<HashSet<_>>()
+        };
+        let g = f.maxcut().unwrap();
+        for v in 0..=8 {
+            assert_eq!(points(&g, v), upper(v), "value {}", v);
+        }
+        // a boolean MDD gives the maximal vectors of the value 0
+        let two = mgr.value(2);
+        let mut h = f.ge(&two);
+        let g = h.maxcut().unwrap();
+        let expected: HashSet<_> = vec![[2, 2, 0], [1, 0, 1], [0, 1, 1], [0, 0, 2]].into_iter().collect();
+        assert_eq!(points(&g, 0), expected);
+        assert_eq!(points(&g, 1), HashSet::new());
+        let mut d = mgr.rpn("x y -", &vars).unwrap();
+        assert!(d.maxcut().is_err());
+    }
+
+    #[test]
+    fn test_mdd_named_states() {
+        let mut mgr: MddMgr<i32> = MddMgr::new();
+        let x = mgr.defvar_states("x", &["failed", "degraded", "nominal"]).unwrap();
+        let y = mgr.defvar_values("y", &[0, 50, 80, 100]).unwrap();
+        assert!(mgr.defvar_values("y", &[0, 50, 80, 100]).is_ok());
+        assert!(mgr.defvar_values("y", &[0, 50, 100]).is_err());
+        let mut f = x.mul(&y);
+        assert_eq!(f.state_index("x", "degraded"), Some(1));
+        assert_eq!(f.state_name("x", 2), Some("nominal".to_string()));
+        // the states of a variable without names are given by their values
+        assert_eq!(f.state_index("y", "80"), Some(2));
+        assert_eq!(f.state_name("y", 3), Some("100".to_string()));
+        assert_eq!(f.state_index("y", "3"), None);
+        let mut pv = HashMap::new();
+        let mut px = HashMap::new();
+        px.insert("failed".to_string(), 0.1);
+        px.insert("degraded".to_string(), 0.2);
+        px.insert("nominal".to_string(), 0.7);
+        pv.insert("x".to_string(), px);
+        let mut py = HashMap::new();
+        py.insert("0".to_string(), 0.1);
+        py.insert("50".to_string(), 0.2);
+        py.insert("80".to_string(), 0.3);
+        py.insert("100".to_string(), 0.4);
+        pv.insert("y".to_string(), py);
+        let result = f.prob_named(&pv, &[200]).unwrap();
+        assert!((result - 0.7 * 0.4).abs() < 1.0e-10);
+        pv.get_mut("x").unwrap().insert("broken".to_string(), 0.0);
+        assert!(f.prob_named(&pv, &[200]).is_err());
+        let ss = vec![160].into_iter().collect::<HashSet<_>>();
+        let paths: Vec<_> = f.mdd_extract(&ss).named().collect();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0]["x"], "nominal");
+        assert_eq!(paths[0]["y"], "80");
+        // the states of a defined variable cannot be changed
+        assert!(mgr.defvar_states("x", &["failed", "degraded", "nominal"]).is_ok());
+        assert!(mgr.defvar_states("x", &["down", "up"]).is_err());
+        assert!(mgr.defvar_domain("y", &["low", "mid", "high", "full"], &[0, 50, 80, 100]).is_err());
+        assert!(mgr.defvar_domain("z", &["down", "up"], &[0]).is_err());
+    }
 }
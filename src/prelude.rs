@@ -41,12 +41,42 @@ pub trait MDDValue: dd::common::TerminalNumberValue + From<i32> + FromStr {}
 impl MDDValue for i64 {}
 impl MDDValue for i32 {}
 
+// terminal values with the arithmetic used by the value distributions and UGF
+pub trait AsF64:
+    Copy + PartialOrd + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Div<Output = Self>
+{
+    fn as_f64(&self) -> f64;
+}
+
+impl AsF64 for i64 {
+    fn as_f64(&self) -> f64 {
+        *self as f64
+    }
+}
+
+impl AsF64 for i32 {
+    fn as_f64(&self) -> f64 {
+        *self as f64
+    }
+}
+
 pub use crate::bss::BddMgr;
 pub use crate::bss::BddNode;
 pub use crate::bdd_path::BddPath;
 pub use crate::bdd_path::ZddPath;
+pub use crate::bdd_ccf::{CcfGroup, CcfModel};
+pub use crate::bdd_phased::PhasedMission;
+pub use crate::bdd_eventtree::EventTree;
+pub use crate::bdd_bounds::Approx;
+pub use crate::bdd_monotone::Violation;
+pub use crate::bdd_equiv::Witness;
 
 pub use crate::mss::MddMgr;
 pub use crate::mss::MddNode;
 pub use crate::mdd_path::MddPath;
 pub use crate::mdd_path::ZMddPath;
+pub use crate::mdd_dist::MddDist;
+pub use crate::mdd_ugf::{ParallelOp, SeriesOp, SpBlock};
+pub use crate::mdd_markov::Ctmc;
+
+pub use crate::zss::{ZddMgr, ZddNode};
@@ -0,0 +1,797 @@
+use crate::prelude::*;
+use std::cmp::Ordering;
+
+// families of sets as zero-suppressed decision diagrams; the node 0 is the empty family
+// and the node 1 is the family {{}}. As in BddManager, a node with a higher level
+// is closer to the root.
+pub const EMPTY: usize = 0;
+pub const BASE: usize = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct ZNode {
+    level: Option<Level>,
+    lo: usize,
+    hi: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum ZddOp {
+    Union,
+    Intersect,
+    Diff,
+    Join,
+    Meet,
+    Minimal,
+    Maximal,
+    SubsetsOf,
+    SupersetsOf,
+    Cardinality,
+}
+
+// one step of a set operation: the result, or the operation whose result is needed next
+enum OpStep {
+    Done(usize),
+    Call(ZddOp, usize, usize),
+}
+
+#[derive(Debug)]
+pub struct ZddManager {
+    nodes: Vec<ZNode>,
+    unique: HashMap<(Level, usize, usize), usize>,
+    labels: HashMap<Level, String>,
+    cache: HashMap<(ZddOp, usize, usize), usize>,
+}
+
+impl Default for ZddManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ZddManager {
+    pub fn new() -> Self {
+        let terminal = ZNode {
+            level: None,
+            lo: EMPTY,
+            hi: EMPTY,
+        };
+        ZddManager {
+            nodes: vec![terminal, terminal],
+            unique: HashMap::new(),
+            labels: HashMap::new(),
+            cache: HashMap::new(),
+        }
+    }
+
+    pub fn empty(&self) -> usize {
+        EMPTY
+    }
+
+    pub fn base(&self) -> usize {
+        BASE
+    }
+
+    pub fn size(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn set_label(&mut self, level: Level, label: &str) {
+        self.labels.insert(level, label.to_string());
+    }
+
+    pub fn label(&self, f: usize) -> Option<&str> {
+        let level = self.level(f)?;
+        self.labels.get(&level).map(|x| x.as_str())
+    }
+
+    pub fn level(&self, f: usize) -> Option<Level> {
+        self.nodes[f].level
+    }
+
+    pub fn lo(&self, f: usize) -> usize {
+        self.nodes[f].lo
+    }
+
+    pub fn hi(&self, f: usize) -> usize {
+        self.nodes[f].hi
+    }
+
+    // the zero-suppression rule: a node whose 1-edge points to the empty family is removed
+    pub fn create_node(&mut self, level: Level, lo: usize, hi: usize) -> usize {
+        if hi == EMPTY {
+            return lo;
+        }
+        let key = (level, lo, hi);
+        if let Some(&x) = self.unique.get(&key) {
+            return x;
+        }
+        let id = self.nodes.len();
+        self.nodes.push(ZNode {
+            level: Some(level),
+            lo,
+            hi,
+        });
+        self.unique.insert(key, id);
+        id
+    }
+
+    // the family {{x1, x2, ...}} of a single set given by levels
+    pub fn single(&mut self, levels: &[Level]) -> usize {
+        let mut levels = levels.to_vec();
+        levels.sort();
+        levels.dedup();
+        let mut result = BASE;
+        for level in levels {
+            result = self.create_node(level, EMPTY, result);
+        }
+        result
+    }
+
+    // labels of all the levels in ascending order
+    pub fn labels(&self) -> Vec<(Level, String)> {
+        let mut result: Vec<(Level, String)> = self.labels.iter().map(|(&l, x)| (l, x.clone())).collect();
+        result.sort();
+        result
+    }
+
+    // read a BDD node as a ZDD; this is how minsol results are interpreted.
+    // zlevel maps the levels of the BDD to the levels of the ZDD
+    pub fn from_bdd(
+        &mut self,
+        dd: &BddManager,
+        node: NodeId,
+        zlevel: &HashMap<Level, Level>,
+        cache: &mut BddHashMap<NodeId, usize>,
+    ) -> usize {
+        let mut next_stack = vec![(node, false)];
+        while let Some((f, expanded)) = next_stack.pop() {
+            if cache.contains_key(&f) {
+                continue;
+            }
+            match dd.get_node(f).unwrap() {
+                bdd::Node::Zero => {
+                    cache.insert(f, EMPTY);
+                }
+                bdd::Node::One => {
+                    cache.insert(f, BASE);
+                }
+                bdd::Node::NonTerminal(fnode) if expanded => {
+                    let level = zlevel[&dd.level(f).unwrap()];
+                    let lo = cache[&fnode[0]];
+                    let hi = cache[&fnode[1]];
+                    let result = self.create_node(level, lo, hi);
+                    cache.insert(f, result);
+                }
+                bdd::Node::NonTerminal(fnode) => {
+                    next_stack.push((f, true));
+                    next_stack.push((fnode[1], false));
+                    next_stack.push((fnode[0], false));
+                }
+                bdd::Node::Undet => panic!("Undetermined node"),
+            }
+        }
+        cache[&node]
+    }
+
+    // the family of the assignments that make the BDD true; universe lists the levels of
+    // all the variables in ascending order, and a variable skipped in the BDD takes both values
+    pub fn from_bdd_function(
+        &mut self,
+        dd: &BddManager,
+        node: NodeId,
+        zlevel: &HashMap<Level, Level>,
+        universe: &[Level],
+        cache: &mut HashMap<(NodeId, usize), usize>,
+    ) -> usize {
+        // (node, i) stands for the node over the first i levels of the universe
+        let mut next_stack = vec![(node, universe.len(), false)];
+        while let Some((f, i, expanded)) = next_stack.pop() {
+            if cache.contains_key(&(f, i)) {
+                continue;
+            }
+            if i == 0 {
+                let result = match dd.get_node(f).unwrap() {
+                    bdd::Node::Zero => EMPTY,
+                    bdd::Node::One => BASE,
+                    _ => panic!("A variable is not in the universe"),
+                };
+                cache.insert((f, i), result);
+                continue;
+            }
+            let level = universe[i - 1];
+            let (f0, f1) = match dd.get_node(f).unwrap() {
+                bdd::Node::NonTerminal(fnode) if zlevel[&dd.level(f).unwrap()] == level => (fnode[0], fnode[1]),
+                bdd::Node::Undet => panic!("Undetermined node"),
+                _ => (f, f),
+            };
+            if expanded {
+                let lo = cache[&(f0, i - 1)];
+                let hi = cache[&(f1, i - 1)];
+                let result = self.create_node(level, lo, hi);
+                cache.insert((f, i), result);
+            } else {
+                next_stack.push((f, i, true));
+                next_stack.push((f1, i - 1, false));
+                next_stack.push((f0, i - 1, false));
+            }
+        }
+        cache[&(node, universe.len())]
+    }
+
+    // the family of the minimal sets making a monotone BDD true;
+    // a set with x is kept only when it has no subset in the family without x
+    pub fn from_bdd_minsol(
+        &mut self,
+        dd: &BddManager,
+        node: NodeId,
+        zlevel: &HashMap<Level, Level>,
+        cache: &mut BddHashMap<NodeId, usize>,
+    ) -> usize {
+        let mut next_stack = vec![(node, false)];
+        while let Some((f, expanded)) = next_stack.pop() {
+            if cache.contains_key(&f) {
+                continue;
+            }
+            match dd.get_node(f).unwrap() {
+                bdd::Node::Zero => {
+                    cache.insert(f, EMPTY);
+                }
+                bdd::Node::One => {
+                    cache.insert(f, BASE);
+                }
+                bdd::Node::NonTerminal(fnode) if expanded => {
+                    let level = zlevel[&dd.level(f).unwrap()];
+                    let lo = cache[&fnode[0]];
+                    let tmp = cache[&fnode[1]];
+                    let sup = self.supersets_of(tmp, lo);
+                    let hi = self.diff(tmp, sup);
+                    let result = self.create_node(level, lo, hi);
+                    cache.insert(f, result);
+                }
+                bdd::Node::NonTerminal(fnode) => {
+                    next_stack.push((f, true));
+                    next_stack.push((fnode[1], false));
+                    next_stack.push((fnode[0], false));
+                }
+                bdd::Node::Undet => panic!("Undetermined node"),
+            }
+        }
+        cache[&node]
+    }
+
+    // the family of the prime implicants of a BDD over literals; lits maps a level of the BDD
+    // to the levels of ~x and x in the ZDD. PI(f) = P + x (PI(f1) - P) + ~x (PI(f0) - P),
+    // where P = PI(f0 & f1)
+    pub fn from_bdd_primes(
+        &mut self,
+        dd: &mut BddManager,
+        node: NodeId,
+        lits: &HashMap<Level, (Level, Level)>,
+        cache: &mut BddHashMap<NodeId, usize>,
+    ) -> usize {
+        // an expanded node keeps f0 & f1 so that it is computed once
+        let mut next_stack = vec![(node, None)];
+        while let Some((f, and)) = next_stack.pop() {
+            if cache.contains_key(&f) {
+                continue;
+            }
+            let (f0, f1) = match dd.get_node(f).unwrap() {
+                bdd::Node::Zero => {
+                    cache.insert(f, EMPTY);
+                    continue;
+                }
+                bdd::Node::One => {
+                    cache.insert(f, BASE);
+                    continue;
+                }
+                bdd::Node::NonTerminal(fnode) => (fnode[0], fnode[1]),
+                bdd::Node::Undet => panic!("Undetermined node"),
+            };
+            match and {
+                Some(g) => {
+                    let (neg, pos) = lits[&dd.level(f).unwrap()];
+                    let p = cache[&g];
+                    let hi0 = self.diff(cache[&f0], p);
+                    let hi1 = self.diff(cache[&f1], p);
+                    let lo = self.create_node(neg, p, hi0);
+                    let result = self.create_node(pos, lo, hi1);
+                    cache.insert(f, result);
+                }
+                None => {
+                    let g = dd.and(f0, f1);
+                    next_stack.push((f, Some(g)));
+                    next_stack.push((f1, None));
+                    next_stack.push((f0, None));
+                    next_stack.push((g, None));
+                }
+            }
+        }
+        cache[&node]
+    }
+
+    // an irredundant subfamily of the cubes in f; a cube is dropped when the OR of the other
+    // cubes kept covers it. literals maps a level of the ZDD to the BDD header and the sign
+    pub fn irredundant_cover(
+        &mut self,
+        f: usize,
+        dd: &mut BddManager,
+        literals: &HashMap<Level, (HeaderId, bool)>,
+    ) -> usize {
+        let mut sets = Vec::new();
+        self.extract_levels(f, &mut Vec::new(), &mut sets);
+        // the cubes with more literals are tried first since they are covered more often
+        sets.sort_by(|a, b| b.len().cmp(&a.len()));
+        let zero = dd.zero();
+        let one = dd.one();
+        let mut cubes = Vec::new();
+        for s in sets.iter() {
+            let mut tmp = one;
+            for level in s.iter() {
+                let (h, positive) = literals[level];
+                let x = if positive {
+                    dd.create_node(h, zero, one)
+                } else {
+                    dd.create_node(h, one, zero)
+                };
+                tmp = dd.and(tmp, x);
+            }
+            cubes.push(tmp);
+        }
+        let mut keep = vec![true; cubes.len()];
+        for i in 0..cubes.len() {
+            let mut others = zero;
+            for j in 0..cubes.len() {
+                if j != i && keep[j] {
+                    others = dd.or(others, cubes[j]);
+                }
+            }
+            let rest = dd.not(others);
+            if dd.and(cubes[i], rest) == zero {
+                keep[i] = false;
+            }
+        }
+        let mut result = EMPTY;
+        for (s, _) in sets.iter().zip(keep.iter()).filter(|(_, &k)| k) {
+            let tmp = self.single(s);
+            result = self.union(result, tmp);
+        }
+        result
+    }
+
+    // the characteristic function of the family; headers lists the BDD headers of
+    // the ZDD levels in ascending order, and a variable skipped in the ZDD takes 0
+    pub fn to_bdd(
+        &self,
+        f: usize,
+        dd: &mut BddManager,
+        headers: &[(Level, HeaderId)],
+        cache: &mut HashMap<(usize, usize), NodeId>,
+    ) -> NodeId {
+        // (f, i) stands for the family over the first i headers; a skipped variable takes 0
+        let mut next_stack = vec![(f, headers.len(), false)];
+        while let Some((g, i, expanded)) = next_stack.pop() {
+            if cache.contains_key(&(g, i)) {
+                continue;
+            }
+            if i == 0 {
+                let result = match g {
+                    EMPTY => dd.zero(),
+                    BASE => dd.one(),
+                    _ => panic!("A variable is not in the universe"),
+                };
+                cache.insert((g, i), result);
+                continue;
+            }
+            let (level, h) = headers[i - 1];
+            let (g0, g1) = if self.level(g) == Some(level) {
+                (self.lo(g), Some(self.hi(g)))
+            } else {
+                (g, None)
+            };
+            if expanded {
+                let lo = cache[&(g0, i - 1)];
+                let hi = match g1 {
+                    Some(g1) => cache[&(g1, i - 1)],
+                    None => dd.zero(),
+                };
+                let result = dd.create_node(h, lo, hi);
+                cache.insert((g, i), result);
+            } else {
+                next_stack.push((g, i, true));
+                if let Some(g1) = g1 {
+                    next_stack.push((g1, i - 1, false));
+                }
+                next_stack.push((g0, i - 1, false));
+            }
+        }
+        cache[&(f, headers.len())]
+    }
+
+    // the results of the set operations are kept until the cache is cleared
+    pub fn clear_cache(&mut self) {
+        self.cache.clear();
+    }
+
+    fn top(&self, f: usize) -> Level {
+        self.level(f).unwrap()
+    }
+
+    fn order(&self, f: usize, g: usize) -> Ordering {
+        self.level(f).cmp(&self.level(g))
+    }
+
+    // the cache key; the arguments of the commutative operations are sorted
+    fn key(op: ZddOp, f: usize, g: usize) -> (ZddOp, usize, usize) {
+        match op {
+            ZddOp::Union | ZddOp::Intersect | ZddOp::Join | ZddOp::Meet if g < f => (op, g, f),
+            _ => (op, f, g),
+        }
+    }
+
+    // the result given by the terminal cases or the cache
+    fn lookup(&self, op: ZddOp, f: usize, g: usize) -> Option<usize> {
+        let result = match op {
+            ZddOp::Union if f == EMPTY => Some(g),
+            ZddOp::Union if g == EMPTY || f == g => Some(f),
+            ZddOp::Intersect if f == EMPTY || g == EMPTY => Some(EMPTY),
+            ZddOp::Intersect if f == g => Some(f),
+            ZddOp::Diff if f == EMPTY || f == g => Some(EMPTY),
+            ZddOp::Diff if g == EMPTY => Some(f),
+            ZddOp::Join if f == EMPTY || g == EMPTY => Some(EMPTY),
+            ZddOp::Join if f == BASE => Some(g),
+            ZddOp::Join if g == BASE => Some(f),
+            ZddOp::Meet if f == EMPTY || g == EMPTY => Some(EMPTY),
+            ZddOp::Meet if f == BASE || g == BASE => Some(BASE),
+            ZddOp::SupersetsOf if f == EMPTY || g == EMPTY => Some(EMPTY),
+            ZddOp::SupersetsOf if g == BASE => Some(f),
+            ZddOp::SubsetsOf if f == EMPTY || g == EMPTY => Some(EMPTY),
+            ZddOp::SubsetsOf if f == BASE => Some(BASE),
+            ZddOp::Minimal | ZddOp::Maximal | ZddOp::Cardinality if f == EMPTY || f == BASE => Some(f),
+            _ => None,
+        };
+        result.or_else(|| self.cache.get(&Self::key(op, f, g)).copied())
+    }
+
+    // one step of the recursion of op; vals are the results of the calls made so far.
+    // g is unused for minimal and maximal, and is the bound k for restrict_cardinality
+    fn step(&mut self, op: ZddOp, f: usize, g: usize, vals: &[usize]) -> OpStep {
+        use std::cmp::Ordering::*;
+        use OpStep::*;
+        match op {
+            ZddOp::Union => match (self.order(f, g), vals) {
+                (Greater, []) => Call(ZddOp::Union, self.lo(f), g),
+                (Greater, &[lo]) => Done(self.create_node(self.top(f), lo, self.hi(f))),
+                (Less, []) => Call(ZddOp::Union, f, self.lo(g)),
+                (Less, &[lo]) => Done(self.create_node(self.top(g), lo, self.hi(g))),
+                (Equal, []) => Call(ZddOp::Union, self.lo(f), self.lo(g)),
+                (Equal, [_]) => Call(ZddOp::Union, self.hi(f), self.hi(g)),
+                (Equal, &[lo, hi]) => Done(self.create_node(self.top(f), lo, hi)),
+                _ => unreachable!(),
+            },
+            ZddOp::Intersect => match (self.order(f, g), vals) {
+                (Greater, []) => Call(ZddOp::Intersect, self.lo(f), g),
+                (Less, []) => Call(ZddOp::Intersect, f, self.lo(g)),
+                (Greater | Less, &[x]) => Done(x),
+                (Equal, []) => Call(ZddOp::Intersect, self.lo(f), self.lo(g)),
+                (Equal, [_]) => Call(ZddOp::Intersect, self.hi(f), self.hi(g)),
+                (Equal, &[lo, hi]) => Done(self.create_node(self.top(f), lo, hi)),
+                _ => unreachable!(),
+            },
+            ZddOp::Diff => match (self.order(f, g), vals) {
+                (Greater, []) => Call(ZddOp::Diff, self.lo(f), g),
+                (Greater, &[lo]) => Done(self.create_node(self.top(f), lo, self.hi(f))),
+                (Less, []) => Call(ZddOp::Diff, f, self.lo(g)),
+                (Less, &[x]) => Done(x),
+                (Equal, []) => Call(ZddOp::Diff, self.lo(f), self.lo(g)),
+                (Equal, [_]) => Call(ZddOp::Diff, self.hi(f), self.hi(g)),
+                (Equal, &[lo, hi]) => Done(self.create_node(self.top(f), lo, hi)),
+                _ => unreachable!(),
+            },
+            ZddOp::Join => match (self.order(f, g), vals) {
+                (Greater, []) => Call(ZddOp::Join, self.lo(f), g),
+                (Greater, [_]) => Call(ZddOp::Join, self.hi(f), g),
+                (Greater, &[lo, hi]) => Done(self.create_node(self.top(f), lo, hi)),
+                (Less, []) => Call(ZddOp::Join, f, self.lo(g)),
+                (Less, [_]) => Call(ZddOp::Join, f, self.hi(g)),
+                (Less, &[lo, hi]) => Done(self.create_node(self.top(g), lo, hi)),
+                // lo = join(f0, g0), hi = join(f1, g1) | join(f1, g0) | join(f0, g1)
+                (Equal, []) => Call(ZddOp::Join, self.lo(f), self.lo(g)),
+                (Equal, [_]) => Call(ZddOp::Join, self.hi(f), self.hi(g)),
+                (Equal, [_, _]) => Call(ZddOp::Join, self.hi(f), self.lo(g)),
+                (Equal, &[_, h11, h10]) => Call(ZddOp::Union, h11, h10),
+                (Equal, [_, _, _, _]) => Call(ZddOp::Join, self.lo(f), self.hi(g)),
+                (Equal, &[_, _, _, tmp, h01]) => Call(ZddOp::Union, tmp, h01),
+                (Equal, &[lo, _, _, _, _, hi]) => Done(self.create_node(self.top(f), lo, hi)),
+                _ => unreachable!(),
+            },
+            ZddOp::Meet => match (self.order(f, g), vals) {
+                (Greater, []) => Call(ZddOp::Union, self.lo(f), self.hi(f)),
+                (Greater, &[tmp]) => Call(ZddOp::Meet, tmp, g),
+                (Less, []) => Call(ZddOp::Union, self.lo(g), self.hi(g)),
+                (Less, &[tmp]) => Call(ZddOp::Meet, f, tmp),
+                (Greater | Less, &[_, x]) => Done(x),
+                // lo = meet(f0, g0) | meet(f1, g0) | meet(f0, g1), hi = meet(f1, g1)
+                (Equal, []) => Call(ZddOp::Meet, self.lo(f), self.lo(g)),
+                (Equal, [_]) => Call(ZddOp::Meet, self.hi(f), self.lo(g)),
+                (Equal, &[m00, m10]) => Call(ZddOp::Union, m00, m10),
+                (Equal, [_, _, _]) => Call(ZddOp::Meet, self.lo(f), self.hi(g)),
+                (Equal, &[_, _, tmp, m01]) => Call(ZddOp::Union, tmp, m01),
+                (Equal, [_, _, _, _, _]) => Call(ZddOp::Meet, self.hi(f), self.hi(g)),
+                (Equal, &[_, _, _, _, lo, hi]) => Done(self.create_node(self.top(f), lo, hi)),
+                _ => unreachable!(),
+            },
+            ZddOp::SupersetsOf => match (self.order(f, g), vals) {
+                (Greater, []) => Call(ZddOp::SupersetsOf, self.lo(f), g),
+                (Greater, [_]) => Call(ZddOp::SupersetsOf, self.hi(f), g),
+                (Greater, &[lo, hi]) => Done(self.create_node(self.top(f), lo, hi)),
+                (Less, []) => Call(ZddOp::SupersetsOf, f, self.lo(g)),
+                (Less, &[x]) => Done(x),
+                (Equal, []) => Call(ZddOp::SupersetsOf, self.lo(f), self.lo(g)),
+                (Equal, [_]) => Call(ZddOp::Union, self.lo(g), self.hi(g)),
+                (Equal, &[_, tmp]) => Call(ZddOp::SupersetsOf, self.hi(f), tmp),
+                (Equal, &[lo, _, hi]) => Done(self.create_node(self.top(f), lo, hi)),
+                _ => unreachable!(),
+            },
+            ZddOp::SubsetsOf => match (self.order(f, g), vals) {
+                (Greater, []) => Call(ZddOp::SubsetsOf, self.lo(f), g),
+                (Greater, &[x]) => Done(x),
+                (Less, []) => Call(ZddOp::Union, self.lo(g), self.hi(g)),
+                (Less, &[tmp]) => Call(ZddOp::SubsetsOf, f, tmp),
+                (Less, &[_, x]) => Done(x),
+                (Equal, []) => Call(ZddOp::Union, self.lo(g), self.hi(g)),
+                (Equal, &[tmp]) => Call(ZddOp::SubsetsOf, self.lo(f), tmp),
+                (Equal, [_, _]) => Call(ZddOp::SubsetsOf, self.hi(f), self.hi(g)),
+                (Equal, &[_, lo, hi]) => Done(self.create_node(self.top(f), lo, hi)),
+                _ => unreachable!(),
+            },
+            // lo = minimal(f0), hi = minimal(f1) - supersets_of(minimal(f1), lo)
+            ZddOp::Minimal => match vals {
+                [] => Call(ZddOp::Minimal, self.lo(f), 0),
+                [_] => Call(ZddOp::Minimal, self.hi(f), 0),
+                &[lo, tmp] => Call(ZddOp::SupersetsOf, tmp, lo),
+                &[_, tmp, sup] => Call(ZddOp::Diff, tmp, sup),
+                &[lo, _, _, hi] => Done(self.create_node(self.top(f), lo, hi)),
+                _ => unreachable!(),
+            },
+            // hi = maximal(f1), lo = maximal(f0) - subsets_of(maximal(f0), hi)
+            ZddOp::Maximal => match vals {
+                [] => Call(ZddOp::Maximal, self.hi(f), 0),
+                [_] => Call(ZddOp::Maximal, self.lo(f), 0),
+                &[hi, tmp] => Call(ZddOp::SubsetsOf, tmp, hi),
+                &[_, tmp, sub] => Call(ZddOp::Diff, tmp, sub),
+                &[hi, _, _, lo] => Done(self.create_node(self.top(f), lo, hi)),
+                _ => unreachable!(),
+            },
+            ZddOp::Cardinality => match vals {
+                [] => Call(ZddOp::Cardinality, self.lo(f), g),
+                &[lo] if g == 0 => Done(self.create_node(self.top(f), lo, EMPTY)),
+                [_] => Call(ZddOp::Cardinality, self.hi(f), g - 1),
+                &[lo, hi] => Done(self.create_node(self.top(f), lo, hi)),
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    // explicit-stack evaluation of the set operations; each frame keeps the results of
+    // the calls made so far, so that the depth is not bounded by the thread stack
+    fn apply(&mut self, op: ZddOp, f: usize, g: usize) -> usize {
+        if let Some(x) = self.lookup(op, f, g) {
+            return x;
+        }
+        let mut next_stack = vec![(Self::key(op, f, g), Vec::new())];
+        loop {
+            let (key, vals) = next_stack.last().unwrap();
+            let key = *key;
+            match self.step(key.0, key.1, key.2, vals) {
+                OpStep::Call(op, f, g) => match self.lookup(op, f, g) {
+                    Some(x) => next_stack.last_mut().unwrap().1.push(x),
+                    None => next_stack.push((Self::key(op, f, g), Vec::new())),
+                },
+                OpStep::Done(x) => {
+                    next_stack.pop();
+                    self.cache.insert(key, x);
+                    match next_stack.last_mut() {
+                        Some((_, vals)) => vals.push(x),
+                        None => return x,
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn union(&mut self, f: usize, g: usize) -> usize {
+        self.apply(ZddOp::Union, f, g)
+    }
+
+    pub fn intersect(&mut self, f: usize, g: usize) -> usize {
+        self.apply(ZddOp::Intersect, f, g)
+    }
+
+    pub fn diff(&mut self, f: usize, g: usize) -> usize {
+        self.apply(ZddOp::Diff, f, g)
+    }
+
+    // {S | T} for all the pairs S in f and T in g
+    pub fn join(&mut self, f: usize, g: usize) -> usize {
+        self.apply(ZddOp::Join, f, g)
+    }
+
+    // {S & T} for all the pairs S in f and T in g
+    pub fn meet(&mut self, f: usize, g: usize) -> usize {
+        self.apply(ZddOp::Meet, f, g)
+    }
+
+    // the sets in f that are a superset of some set in g
+    pub fn supersets_of(&mut self, f: usize, g: usize) -> usize {
+        self.apply(ZddOp::SupersetsOf, f, g)
+    }
+
+    // the sets in f that are a subset of some set in g
+    pub fn subsets_of(&mut self, f: usize, g: usize) -> usize {
+        self.apply(ZddOp::SubsetsOf, f, g)
+    }
+
+    // the sets in f that have no proper subset in f
+    pub fn minimal(&mut self, f: usize) -> usize {
+        self.apply(ZddOp::Minimal, f, 0)
+    }
+
+    // the sets in f that have no proper superset in f
+    pub fn maximal(&mut self, f: usize) -> usize {
+        self.apply(ZddOp::Maximal, f, 0)
+    }
+
+    // the sets in f whose cardinality is at most k
+    pub fn restrict_cardinality(&mut self, f: usize, k: usize) -> usize {
+        self.apply(ZddOp::Cardinality, f, k)
+    }
+
+    pub fn count<T>(&self, f: usize, cache: &mut HashMap<usize, T>) -> T
+    where
+        T: Add<Output = T> + Clone + From<u32>,
+    {
+        let mut next_stack = vec![(f, false)];
+        while let Some((g, expanded)) = next_stack.pop() {
+            if cache.contains_key(&g) {
+                continue;
+            }
+            match g {
+                EMPTY => {
+                    cache.insert(g, T::from(0));
+                }
+                BASE => {
+                    cache.insert(g, T::from(1));
+                }
+                _ if expanded => {
+                    let lo = cache.get(&self.lo(g)).unwrap().clone();
+                    let hi = cache.get(&self.hi(g)).unwrap().clone();
+                    cache.insert(g, lo + hi);
+                }
+                _ => {
+                    next_stack.push((g, true));
+                    next_stack.push((self.hi(g), false));
+                    next_stack.push((self.lo(g), false));
+                }
+            }
+        }
+        cache.get(&f).unwrap().clone()
+    }
+
+    // the levels appearing in the sets of f
+    pub fn support(&self, f: usize) -> HashSet<Level> {
+        let mut result = HashSet::new();
+        let mut visited = HashSet::new();
+        let mut next_stack = vec![f];
+        while let Some(g) = next_stack.pop() {
+            if g == EMPTY || g == BASE || !visited.insert(g) {
+                continue;
+            }
+            result.insert(self.top(g));
+            next_stack.push(self.lo(g));
+            next_stack.push(self.hi(g));
+        }
+        result
+    }
+
+    // all the sets; each set lists the labels from the top level
+    pub fn extract(&self, f: usize) -> Vec<Vec<String>> {
+        let mut result = Vec::new();
+        self.extract_levels(f, &mut Vec::new(), &mut result);
+        result
+            .into_iter()
+            .map(|s| {
+                s.iter()
+                    .map(|l| self.labels.get(l).map(|x| x.as_str()).unwrap_or("?").to_string())
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn extract_levels(&self, f: usize, path: &mut Vec<Level>, result: &mut Vec<Vec<Level>>) {
+        // the sets with the lower edge are listed first as in the recursive order
+        let mut next_stack = vec![ExtractStep::Visit(f)];
+        while let Some(step) = next_stack.pop() {
+            match step {
+                ExtractStep::Visit(EMPTY) => (),
+                ExtractStep::Visit(BASE) => result.push(path.clone()),
+                ExtractStep::Visit(g) => {
+                    next_stack.push(ExtractStep::Pop);
+                    next_stack.push(ExtractStep::Visit(self.hi(g)));
+                    next_stack.push(ExtractStep::Push(self.level(g).unwrap()));
+                    next_stack.push(ExtractStep::Visit(self.lo(g)));
+                }
+                ExtractStep::Push(level) => path.push(level),
+                ExtractStep::Pop => {
+                    path.pop();
+                }
+            }
+        }
+    }
+}
+
+enum ExtractStep {
+    Visit(usize),
+    Push(Level),
+    Pop,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sets(z: &ZddManager, f: usize) -> Vec<Vec<Level>> {
+        let mut result: Vec<Vec<Level>> = z
+            .extract(f)
+            .into_iter()
+            .map(|s| {
+                let mut s: Vec<Level> = s.iter().map(|x| x[1..].parse().unwrap()).collect();
+                s.sort();
+                s
+            })
+            .collect();
+        result.sort();
+        result
+    }
+
+    #[test]
+    fn test_family_ops() {
+        let mut z = ZddManager::new();
+        for i in 0..4 {
+            z.set_label(i, &format!("x{}", i));
+        }
+        // f = {{0, 1}, {1, 2}, {0, 2}}, g = {{0}, {1, 2}, {0, 1, 3}}
+        let s01 = z.single(&[0, 1]);
+        let s12 = z.single(&[1, 2]);
+        let s02 = z.single(&[0, 2]);
+        let tmp = z.union(s01, s12);
+        let f = z.union(tmp, s02);
+        let s0 = z.single(&[0]);
+        let s013 = z.single(&[0, 1, 3]);
+        let tmp = z.union(s0, s12);
+        let g = z.union(tmp, s013);
+        let mut cache = HashMap::new();
+        assert_eq!(z.count::<u64>(f, &mut cache), 3);
+        let x = z.intersect(f, g);
+        assert_eq!(sets(&z, x), vec![vec![1, 2]]);
+        let x = z.diff(f, g);
+        assert_eq!(sets(&z, x), vec![vec![0, 1], vec![0, 2]]);
+        let x = z.supersets_of(f, s0);
+        assert_eq!(sets(&z, x), vec![vec![0, 1], vec![0, 2]]);
+        let x = z.subsets_of(f, s013);
+        assert_eq!(sets(&z, x), vec![vec![0, 1]]);
+        let x = z.join(s0, s12);
+        assert_eq!(sets(&z, x), vec![vec![0, 1, 2]]);
+        let x = z.meet(f, s013);
+        assert_eq!(sets(&z, x), vec![vec![0], vec![0, 1], vec![1]]);
+        let x = z.union(f, g);
+        let m = z.minimal(x);
+        assert_eq!(sets(&z, m), vec![vec![0], vec![1, 2]]);
+        let m = z.maximal(x);
+        assert_eq!(sets(&z, m), vec![vec![0, 1, 3], vec![0, 2], vec![1, 2]]);
+        let x = z.restrict_cardinality(g, 2);
+        assert_eq!(sets(&z, x), vec![vec![0], vec![1, 2]]);
+        // {} and {x0} are different families
+        let x = z.union(BASE, s0);
+        assert_eq!(sets(&z, x), vec![vec![], vec![0]]);
+    }
+}
@@ -0,0 +1,420 @@
+use crate::prelude::*;
+
+use crate::zdd::ZddManager;
+use crate::zdd;
+use crate::bdd_equiv;
+use crate::bdd_monotone::variables;
+
+pub struct ZddMgr {
+    zdd: Rc<RefCell<ZddManager>>,
+    vars: HashMap<String, Level>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ZddNode {
+    parent: Weak<RefCell<ZddManager>>,
+    node: usize,
+}
+
+impl ZddMgr {
+    // constructor
+    pub fn new() -> Self {
+        ZddMgr {
+            zdd: Rc::new(RefCell::new(ZddManager::new())),
+            vars: HashMap::default(),
+        }
+    }
+
+    // size
+    pub fn size(&self) -> usize {
+        self.zdd.borrow().size()
+    }
+
+    pub fn empty(&self) -> ZddNode {
+        ZddNode::new(&self.zdd, zdd::EMPTY)
+    }
+
+    pub fn base(&self) -> ZddNode {
+        ZddNode::new(&self.zdd, zdd::BASE)
+    }
+
+    // the family {{var}}
+    pub fn defvar(&mut self, var: &str) -> ZddNode {
+        let level = self.var_level(var);
+        let node = self.zdd.borrow_mut().single(&[level]);
+        ZddNode::new(&self.zdd, node)
+    }
+
+    // a variable x takes an odd level and the literal ~x takes the level just below x,
+    // so that ~x is available for any variable without changing the order of the variables
+    fn var_level(&mut self, var: &str) -> Level {
+        if let Some(x) = var.strip_prefix('~').filter(|x| !x.starts_with('~')) {
+            let level = self.var_level(x) - 1;
+            self.zdd.borrow_mut().set_label(level, var);
+            return level;
+        }
+        if let Some(&level) = self.vars.get(var) {
+            return level;
+        }
+        let level = 2 * self.vars.len() + 1;
+        self.zdd.borrow_mut().set_label(level, var);
+        self.vars.insert(var.to_string(), level);
+        level
+    }
+
+    pub fn get_varorder(&self) -> Vec<String> {
+        let mut result: Vec<(Level, String)> = self.vars.iter().map(|(k, &v)| (v, k.clone())).collect();
+        result.sort();
+        result.into_iter().map(|(_, k)| k).collect()
+    }
+
+    // the family consisting of the given sets of variables
+    pub fn sets(&mut self, sets: &[Vec<&str>]) -> ZddNode {
+        let mut result = zdd::EMPTY;
+        for s in sets.iter() {
+            let levels: Vec<Level> = s.iter().map(|x| self.var_level(x)).collect();
+            let mut zdd = self.zdd.borrow_mut();
+            let tmp = zdd.single(&levels);
+            result = zdd.union(result, tmp);
+        }
+        self.zdd.borrow_mut().clear_cache();
+        ZddNode::new(&self.zdd, result)
+    }
+
+    // the levels of the variables in the BDD; undefined variables are defined
+    // in the order of the BDD, and the orders should be consistent
+    fn import_levels(&mut self, node: &BddNode) -> Result<HashMap<Level, Level>, String> {
+        let mut result = HashMap::new();
+        let mut last = None;
+        for (level, label, _) in bdd_vars(node) {
+            let zlevel = self.var_level(&label);
+            if last.is_some() && Some(zlevel) <= last {
+                return Err(format!("The order of {} is inconsistent with the BDD", label));
+            }
+            last = Some(zlevel);
+            result.insert(level, zlevel);
+        }
+        Ok(result)
+    }
+
+    // the levels of the literals ~x and x for the variables in the BDD
+    fn import_literals(&mut self, vars: &[(Level, String, HeaderId)]) -> Result<HashMap<Level, (Level, Level)>, String> {
+        let mut result = HashMap::new();
+        let mut last = None;
+        for (level, label, _) in vars.iter() {
+            let neg = self.var_level(&format!("~{}", label));
+            let pos = self.var_level(label);
+            if last.is_some() && Some(neg) <= last {
+                return Err(format!("The order of {} is inconsistent with the BDD", label));
+            }
+            last = Some(pos);
+            result.insert(*level, (neg, pos));
+        }
+        Ok(result)
+    }
+
+    // read a BDD node as a ZDD as zdd_extract does, e.g., the result of minpath
+    pub fn from_bdd(&mut self, node: &BddNode) -> Result<ZddNode, String> {
+        let zlevel = self.import_levels(node)?;
+        let bdd = node.get_mgr();
+        let mut cache = BddHashMap::default();
+        let result = self.zdd.borrow_mut().from_bdd(&bdd.borrow(), node.get_id(), &zlevel, &mut cache);
+        Ok(ZddNode::new(&self.zdd, result))
+    }
+
+    // the family of the assignments making the BDD true over all the variables of this manager
+    pub fn from_bdd_function(&mut self, node: &BddNode) -> Result<ZddNode, String> {
+        let zlevel = self.import_levels(node)?;
+        let mut universe: Vec<Level> = self.vars.values().cloned().collect();
+        universe.sort();
+        let bdd = node.get_mgr();
+        let mut cache = HashMap::new();
+        let result = self
+            .zdd
+            .borrow_mut()
+            .from_bdd_function(&bdd.borrow(), node.get_id(), &zlevel, &universe, &mut cache);
+        Ok(ZddNode::new(&self.zdd, result))
+    }
+
+    // the minimal sets of variables making a monotone BDD true
+    pub fn from_bdd_minsol(&mut self, node: &BddNode) -> Result<ZddNode, String> {
+        let zlevel = self.import_levels(node)?;
+        let bdd = node.get_mgr();
+        let mut cache = BddHashMap::default();
+        let result = self
+            .zdd
+            .borrow_mut()
+            .from_bdd_minsol(&bdd.borrow(), node.get_id(), &zlevel, &mut cache);
+        self.zdd.borrow_mut().clear_cache();
+        Ok(ZddNode::new(&self.zdd, result))
+    }
+
+    // the prime implicants of the BDD as a family over the literals x and ~x
+    pub fn prime_implicants(&mut self, node: &BddNode) -> Result<ZddNode, String> {
+        let vars = bdd_vars(node);
+        let lits = self.import_literals(&vars)?;
+        let bdd = node.get_mgr();
+        let mut cache = BddHashMap::default();
+        let result = self
+            .zdd
+            .borrow_mut()
+            .from_bdd_primes(&mut bdd.borrow_mut(), node.get_id(), &lits, &mut cache);
+        self.zdd.borrow_mut().clear_cache();
+        Ok(ZddNode::new(&self.zdd, result))
+    }
+
+    // an irredundant cover of the BDD chosen from the prime implicants
+    pub fn prime_cover(&mut self, node: &BddNode) -> Result<ZddNode, String> {
+        let vars = bdd_vars(node);
+        let lits = self.import_literals(&vars)?;
+        let mut literals = HashMap::new();
+        for (level, _, h) in vars.iter() {
+            let (neg, pos) = lits[level];
+            literals.insert(neg, (*h, false));
+            literals.insert(pos, (*h, true));
+        }
+        let bdd = node.get_mgr();
+        let mut dd = bdd.borrow_mut();
+        let mut zdd = self.zdd.borrow_mut();
+        let mut cache = BddHashMap::default();
+        let primes = zdd.from_bdd_primes(&mut dd, node.get_id(), &lits, &mut cache);
+        let result = zdd.irredundant_cover(primes, &mut dd, &literals);
+        zdd.clear_cache();
+        Ok(ZddNode::new(&self.zdd, result))
+    }
+}
+
+// the variables in the BDD as (level, label, header) in ascending order of levels
+fn bdd_vars(node: &BddNode) -> Vec<(Level, String, HeaderId)> {
+    let bdd = node.get_mgr();
+    let dd = bdd.borrow();
+    variables(&|x| bdd_equiv::step(&dd, x), node.get_id())
+        .into_iter()
+        .map(|(level, label, f)| (level, label, dd.get_node(f).unwrap().headerid().unwrap()))
+        .collect()
+}
+
+impl Default for ZddMgr {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ZddNode {
+    pub fn new(zdd: &Rc<RefCell<ZddManager>>, node: usize) -> Self {
+        ZddNode {
+            parent: Rc::downgrade(zdd),
+            node,
+        }
+    }
+
+    pub fn get_mgr(&self) -> Rc<RefCell<ZddManager>> {
+        self.parent.upgrade().unwrap()
+    }
+
+    pub fn get_id(&self) -> usize {
+        self.node
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.node == zdd::EMPTY
+    }
+
+    pub fn is_base(&self) -> bool {
+        self.node == zdd::BASE
+    }
+
+    fn apply<F>(&self, op: F) -> ZddNode
+    where
+        F: FnOnce(&mut ZddManager, usize) -> usize,
+    {
+        let zddmgr = self.parent.upgrade().unwrap();
+        let mut zdd = zddmgr.borrow_mut();
+        let node = op(&mut zdd, self.node);
+        // the op cache is kept only during a top-level operation
+        zdd.clear_cache();
+        ZddNode::new(&zddmgr, node)
+    }
+
+    pub fn union(&self, other: &ZddNode) -> ZddNode {
+        self.apply(|z, f| z.union(f, other.node))
+    }
+
+    pub fn intersect(&self, other: &ZddNode) -> ZddNode {
+        self.apply(|z, f| z.intersect(f, other.node))
+    }
+
+    pub fn diff(&self, other: &ZddNode) -> ZddNode {
+        self.apply(|z, f| z.diff(f, other.node))
+    }
+
+    pub fn join(&self, other: &ZddNode) -> ZddNode {
+        self.apply(|z, f| z.join(f, other.node))
+    }
+
+    pub fn meet(&self, other: &ZddNode) -> ZddNode {
+        self.apply(|z, f| z.meet(f, other.node))
+    }
+
+    pub fn minimal(&self) -> ZddNode {
+        self.apply(|z, f| z.minimal(f))
+    }
+
+    pub fn maximal(&self) -> ZddNode {
+        self.apply(|z, f| z.maximal(f))
+    }
+
+    // the sets that are a subset of some set in other
+    pub fn subsets_of(&self, other: &ZddNode) -> ZddNode {
+        self.apply(|z, f| z.subsets_of(f, other.node))
+    }
+
+    // the sets that are a superset of some set in other
+    pub fn supersets_of(&self, other: &ZddNode) -> ZddNode {
+        self.apply(|z, f| z.supersets_of(f, other.node))
+    }
+
+    // the sets with at most k elements
+    pub fn restrict_cardinality(&self, k: usize) -> ZddNode {
+        self.apply(|z, f| z.restrict_cardinality(f, k))
+    }
+
+    pub fn count(&self) -> u64 {
+        let zddmgr = self.parent.upgrade().unwrap();
+        let mut cache = HashMap::new();
+        let result = zddmgr.borrow().count(self.node, &mut cache);
+        result
+    }
+
+    pub fn extract(&self) -> Vec<Vec<String>> {
+        let zddmgr = self.parent.upgrade().unwrap();
+        let result = zddmgr.borrow().extract(self.node);
+        result
+    }
+
+    pub fn eq(&self, other: &ZddNode) -> bool {
+        self.node == other.node
+    }
+
+    // the characteristic function of the family over all the variables of the manager;
+    // the variables are defined in bss in the order of the ZDD when they are missing
+    pub fn to_bdd(&self, bss: &mut BddMgr) -> Result<BddNode, String> {
+        let zddmgr = self.parent.upgrade().unwrap();
+        // the labels are copied since bss may share the ZDD manager
+        let labels = zddmgr.borrow().labels();
+        // the literals ~x have no counterpart in the BDD, e.g., the prime implicants
+        let support = zddmgr.borrow().support(self.node);
+        for (level, label) in labels.iter() {
+            if label.starts_with('~') && support.contains(level) {
+                return Err(format!("The family has the literal {}, which is not a variable", label));
+            }
+        }
+        let mut headers = Vec::new();
+        let mut last = None;
+        for (level, label) in labels {
+            if label.starts_with('~') {
+                continue;
+            }
+            let x = bss.defvar(&label);
+            if x.get_label().as_deref() != Some(label.as_str()) {
+                return Err(format!("{} is not a variable", label));
+            }
+            let blevel = x.get_level();
+            if last.is_some() && blevel <= last {
+                return Err(format!("The order of {} is inconsistent with the ZDD", label));
+            }
+            last = blevel;
+            headers.push((level, x.get_header().unwrap()));
+        }
+        let bdd = bss.zero().get_mgr();
+        let mut cache = HashMap::new();
+        let node = zddmgr
+            .borrow()
+            .to_bdd(self.node, &mut bdd.borrow_mut(), &headers, &mut cache);
+        Ok(BddNode::new(&bdd, node))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zdd_mgr() {
+        let mut zmgr = ZddMgr::new();
+        let a = zmgr.defvar("a");
+        let b = zmgr.defvar("b");
+        let c = zmgr.defvar("c");
+        let f = a.join(&b).union(&c);
+        println!("{:?}", f.extract());
+        assert_eq!(f.count(), 2);
+        assert!(f.eq(&zmgr.sets(&[vec!["a", "b"], vec!["c"]])));
+        // {} and {c} are kept apart by the zero-suppression rule
+        let g = zmgr.base().union(&c);
+        assert_eq!(g.count(), 2);
+        assert!(zmgr.empty().is_empty());
+        assert_eq!(zmgr.get_varorder(), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_bdd_conversion() {
+        let mut bss = BddMgr::new();
+        let f = bss.rpn("a b & c |").unwrap();
+        let mut zmgr = ZddMgr::new();
+        let z = f.to_zdd(&mut zmgr).unwrap();
+        println!("{:?}", z.extract());
+        assert_eq!(z.count(), 5);
+        let g = z.to_bdd(&mut bss).unwrap();
+        assert_eq!(g.get_id(), f.get_id());
+        let m = f.minpath_zdd(&mut zmgr).unwrap();
+        assert_eq!(m.count(), 2);
+        assert!(m.eq(&z.minimal()));
+        assert!(m.eq(&zmgr.from_bdd(&f.minpath().unwrap()).unwrap()));
+        // the order of c and a conflicts with the BDD
+        let mut zmgr2 = ZddMgr::new();
+        zmgr2.defvar("c");
+        zmgr2.defvar("a");
+        assert!(f.to_zdd(&mut zmgr2).is_err());
+    }
+
+    #[test]
+    fn test_prime_implicants() {
+        let mut bss = BddMgr::new();
+        let f = bss.rpn("a b ~ & a ~ c & |").unwrap();
+        let mut zmgr = ZddMgr::new();
+        let primes = f.prime_implicants(&mut zmgr).unwrap();
+        let mut result: Vec<Vec<String>> = primes
+            .extract()
+            .into_iter()
+            .map(|mut s| {
+                s.sort();
+                s
+            })
+            .collect();
+        result.sort();
+        println!("{:?}", result);
+        // ~b c is the consensus of a b~ and a~ c
+        assert_eq!(result, vec![vec!["a", "~b"], vec!["c", "~a"], vec!["c", "~b"]]);
+        let cover = f.prime_cover(&mut zmgr).unwrap();
+        assert_eq!(cover.count(), 2);
+        assert!(cover.eq(&zmgr.sets(&[vec!["a", "~b"], vec!["~a", "c"]])));
+        // for a monotone function, the prime implicants are the minimal paths
+        let g = bss.rpn("a b & c |").unwrap();
+        let primes = g.prime_implicants(&mut zmgr).unwrap();
+        assert_eq!(primes.count(), 2);
+        assert!(primes.eq(&zmgr.sets(&[vec!["a", "b"], vec!["c"]])));
+        // the literals are available in the ZDD manager mirroring the BDD variables
+        let primes = f.prime_implicants(bss.zdd()).unwrap();
+        assert_eq!(primes.count(), 3);
+        assert!(primes.eq(&bss.zdd().sets(&[vec!["a", "~b"], vec!["~a", "c"], vec!["~b", "c"]])));
+        let cover = f.prime_cover(bss.zdd()).unwrap();
+        assert_eq!(cover.count(), 2);
+        // the families over the literals are not characteristic functions of the variables
+        assert!(primes.to_bdd(&mut bss).is_err());
+        assert!(cover.to_bdd(&mut bss).is_err());
+        let g = bss.rpn("a b & c |").unwrap();
+        let z = g.to_zdd(bss.zdd()).unwrap();
+        assert_eq!(z.to_bdd(&mut bss).unwrap().get_id(), g.get_id());
+        assert_eq!(bss.zdd().get_varorder(), vec!["a", "b", "c"]);
+    }
+}